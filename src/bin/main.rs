@@ -1,8 +1,108 @@
-use cli_chess_rust::chess::Chess;
+use std::{
+    env,
+    process
+};
+use cli_chess_rust::{
+    game::Game,
+    display::{
+        DisplayOptions,
+        Orientation
+    },
+    engine::Engine,
+    book::Book,
+    difficulty::Difficulty,
+    uci::Uci
+};
+#[cfg(feature = "serde")]
+use cli_chess_rust::eval::EvalParams;
 
 fn main() {
 
-    let mut cli_chess = Chess::new();
+    let mut cli_chess = Game::new();
+    let mut options = DisplayOptions::default();
+    let mut engine = Engine::new();
+    let mut uci = false;
+    #[cfg(feature = "tui")]
+    let mut tui = false;
 
-    cli_chess.play();
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            #[cfg(feature = "serde")]
+            "--resume" => {
+                let path = args.next().unwrap_or_else(|| usage());
+                cli_chess = Game::load(&path).unwrap_or_else(|e| {
+                    eprintln!("Couldn't resume the game from '{}': {}", path, e);
+                    process::exit(1)
+                });
+            },
+            "--unicode" => { options.unicode = true; },
+            "--color" => { options.colored = true; },
+            #[cfg(feature = "tui")]
+            "--tui" => { tui = true; },
+            "--threads" => {
+                match args.next().and_then(|n| n.parse::<usize>().ok() ) {
+                    Some(threads) if threads > 0 => engine.set_threads(threads),
+                    _ => usage()
+                }
+            },
+            "--difficulty" => {
+                match args.next().as_deref().and_then(Difficulty::from_name) {
+                    Some(difficulty) => engine.set_difficulty(difficulty),
+                    None => usage()
+                }
+            },
+            "--book" => {
+                let path = args.next().unwrap_or_else(|| usage());
+                engine.set_book(Some(Book::open(&path).unwrap_or_else(|e| {
+                    eprintln!("Couldn't open the book '{}': {}", path, e);
+                    process::exit(1)
+                })));
+            },
+            "--uci" => { uci = true; },
+            #[cfg(feature = "serde")]
+            "--eval" => {
+                let path = args.next().unwrap_or_else(|| usage());
+                engine.set_params(EvalParams::load(&path).unwrap_or_else(|e| {
+                    eprintln!("Couldn't read the evaluation parameters from '{}': {}", path, e);
+                    process::exit(1)
+                }));
+            },
+            "--orientation" => {
+                options.orientation = match args.next().as_deref() {
+                    Some("white") => Orientation::White,
+                    Some("black") => Orientation::Black,
+                    Some("turn") => Orientation::Turn,
+                    _ => usage()
+                };
+            },
+            _ => usage()
+        }
+    }
+
+    if uci {
+        if let Err(e) = Uci::new(engine).run() {
+            eprintln!("Couldn't read the input: {}", e);
+            process::exit(1)
+        }
+        return
+    }
+
+    #[cfg(feature = "tui")]
+    if tui {
+        if let Err(e) = cli_chess.play_tui(options) {
+            eprintln!("Terminal error: {}", e);
+            process::exit(1)
+        }
+        return
+    }
+
+    cli_chess.play_with_engine(options, &engine);
+}
+
+fn usage() -> ! {
+
+    eprintln!("Usage: run_cli_chess [--resume <file>] [--unicode] [--color] [--orientation <white|black|turn>] [--tui] [--threads <n>] [--difficulty <beginner|casual|intermediate|advanced|master>] [--eval <file>] [--book <file>] [--uci]");
+    process::exit(1)
 }
\ No newline at end of file
@@ -0,0 +1,80 @@
+use std::{
+    env,
+    fs,
+    process
+};
+use cli_chess_rust::{
+    eval::EvalParams,
+    tune::{
+        Tuner,
+        read_epd,
+        read_pgn
+    }
+};
+
+fn main() {
+
+    let mut params = EvalParams::default();
+    let mut output = String::from("eval.json");
+    let mut passes = 100;
+    let mut positions = Vec::new();
+
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--params" => {
+                let path = args.next().unwrap_or_else(|| usage());
+                params = EvalParams::load(&path).unwrap_or_else(|e| {
+                    eprintln!("Couldn't read the parameters from '{}': {}", path, e);
+                    process::exit(1)
+                });
+            },
+            "--output" => { output = args.next().unwrap_or_else(|| usage()); },
+            "--passes" => { passes = args.next().and_then(|n| n.parse().ok() ).unwrap_or_else(|| usage()); },
+            path if path.ends_with(".pgn") || path.ends_with(".epd") => {
+                let text = fs::read_to_string(path).unwrap_or_else(|e| {
+                    eprintln!("Couldn't read '{}': {}", path, e);
+                    process::exit(1)
+                });
+                let read = if path.ends_with(".pgn") { read_pgn(&text) } else { read_epd(&text) };
+                positions.extend(read.unwrap_or_else(|e| {
+                    eprintln!("Couldn't read '{}': {}", path, e);
+                    process::exit(1)
+                }));
+            },
+            _ => usage()
+        }
+    }
+
+    if positions.is_empty() {
+        usage()
+    }
+
+    let mut tuner = Tuner::new(positions);
+    let mut error = tuner.fit_scale(&params);
+
+    println!("{} positions, scale {:.2}, error {:.6}", tuner.len(), tuner.scale(), error);
+
+    for pass in 1..=passes {
+
+        let tuned = tuner.tune_pass(&mut params, error);
+
+        if let Err(e) = params.save(&output) {
+            eprintln!("Couldn't write the parameters to '{}': {}", output, e);
+            process::exit(1)
+        }
+        println!("Pass {}: error {:.6}, written to '{}'", pass, tuned, output);
+
+        if tuned >= error {
+            break
+        }
+        error = tuned;
+    }
+}
+
+fn usage() -> ! {
+
+    eprintln!("Usage: tune_cli_chess <file.pgn|file.epd>... [--params <file>] [--output <file>] [--passes <n>]");
+    process::exit(1)
+}
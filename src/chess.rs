@@ -1,4 +1,3 @@
-use std::io;
 use crate::{
     r#move::{
         Move,
@@ -6,14 +5,17 @@ use crate::{
     },
     player::Player,
     piece::Piece,
-    constant::{
-        INIT_BOARD,
-        DIAGONALS,
-        STRAIGHTS
-    }
+    square::{
+        Square,
+        Rank
+    },
+    position::Position,
+    error::ChessError
 };
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "state", rename_all = "snake_case"))]
 pub enum ChessState {
     Checkmate,
     Check {
@@ -24,268 +26,160 @@ pub enum ChessState {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Chess {
-    pub board: [[Option<Piece>; 8]; 8],
-    pub turn: Player,
-    pub state: ChessState,
-    pub moves: Vec<Move>
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub position: Position,
+    pub state: ChessState
+}
+
+impl Default for Chess {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Chess {
     pub fn new() -> Chess {
 
-        Chess {
-            board: INIT_BOARD,
-            turn: Player::White,
-            state: ChessState::Normal,
-            moves: Vec::new()
-        }
+        Chess::from_position(Position::new())
     }
 
-    pub fn play(&mut self) {
+    pub fn from_position(position: Position) -> Chess {
 
-        println!("To move a piece, type it's current and new spot (e.g 'A1 A2')");
-
-        let mut turn: u8 = 1;
+        Chess {
+            position,
+            state: ChessState::Normal
+        }
+    }
 
-        loop {
+    pub fn make_move(&mut self, m: &Move) -> Result<ChessState, ChessError> {
 
-            println!("{}", self);
+        let state = m.process_move(self, None)?;
 
-            if turn % 2 == 1 {
-                self.turn = Player::White;
-            } else {
-                self.turn = Player::Black;
-            }
-            println!("{}'s turn.", self.turn);
-
-            let mut input = String::new();
-            io::stdin().read_line(&mut input).expect("Failed to read line");
-
-            let m = match Move::from_input(&self, input) {
-                Ok(m) => m,
-                Err(e) => {
-                    println!("{}", e);
-                    continue
-                }
-            };
-
-            match m.process_move(self, None)  {
-                Ok(state) => match state {
-                    ChessState::Checkmate => {
-                        println!("Checkmate! {} won the game.", self.turn);
-                        println!("{}", self);
-                        break
-                    },
-                    ChessState::Check {
-                        checked_player,
-                        moves_left: _
-                    } => {
-                        println!("{} is in a check.", checked_player);
-                        self.state = state;
-                    },
-                    ChessState::Normal => { self.state = state; }
-                },
-                Err(e) => {
-                    println!("{}", e);
-                    continue
-                }
-            }
-
-            turn += 1;
+        self.state = state.clone();
+        if self.position.turn == Player::Black {
+            self.position.fullmove_number += 1;
         }
+        self.position.turn = self.position.turn.opponent();
+
+        Ok(state)
     }
 
+    // the checking player's pieces that attack the other king, after the test move if one is given
     pub fn is_check(
         &self,
         checking_player: &Player,
         test: Option<&Move>
     ) -> Option<Vec<Move>> {
 
-        let mut king_tile = self.find_king(&checking_player.opponent());
+        let position = match test {
+            Some(m) => self.board_after(m),
+            None => self.position
+        };
 
-        if let Some(m) = test {
+        let king_tile = position.find_king(&checking_player.opponent());
 
-            let mut test_chess = self.clone();
-
-            test_chess.board[m.from.0][m.from.1] = None;
-            test_chess.board[m.to.0][m.to.1] = Some(m.piece);
-
-            king_tile = test_chess.find_king(&checking_player.opponent());
-
-            return test_chess.piece_can_move_to(&checking_player, vec![king_tile], false)
-        }
-
-        self.piece_can_move_to(&checking_player, vec![king_tile], false)
-    }
-
-    pub fn piece_can_move_to(
-        &self,
-        player: &Player,
-        tiles: Vec<(usize, usize)>,
-        is_check_block: bool
-    ) -> Option<Vec<Move>> {
-
-        let checking_moves: Vec<Move> = (0..8)
-            .flat_map(|x| (0..8).filter_map(move |y| match self.board[x][y] {
-                Some(piece) => if Piece::get_pieces(player)[0..=4].contains(&piece) {
-                    Some((piece, (x, y)))
-                } else {
-                    None
-                },
-                None => None
-            }))
-            .map(|(p, from)| p.possible_moves(&from) )
-            .flat_map(|moves| moves )
-            .filter_map(|m| if let Ok(move_type) = m.is_valid_move(&mut self.clone(), Some(player)) {
-                if is_check_block { // any move to block the check
-                    Some(m)
-                } else { // moves that compromise king or castling path
-                    match move_type {
-                        MoveType::PawnEat | MoveType::Other => Some(m),
-                        _ => None
-                    }
-                }
-            } else {
-                None
-            })
-            .filter(|m| tiles
-                .iter()
-                .any(|t| *t == m.to )
-            )
+        let checking_moves: Vec<Move> = position
+            .attackers(&king_tile, checking_player)
+            .into_iter()
+            .filter_map(|from| position.board[from].map(|piece| Move::new(piece, from, king_tile) ) )
             .collect()
         ;
-        
-        if let false = checking_moves.is_empty() {
+
+        if !checking_moves.is_empty() {
             Some(checking_moves)
         } else {
             None
         }
     }
 
-    pub fn find_path(
-        &self,
-        from: &(usize, usize),
-        to: &(usize, usize)
-    ) -> Vec<(usize, usize)> {
-
-        let (from, to) = ((from.0 as i8, from.1 as i8), (to.0 as i8, to.1 as i8));
-        let m = ((to.0 - from.0), (to.1 - from.1));
-
-        [DIAGONALS, STRAIGHTS]
-            .iter()
-            .flat_map(|a| a )
-            .filter(|a| a.contains(&m) )
-            .flat_map(|a| a )
-            .take_while(|i| *i != &m )
-            .map(|m| {
-                let m = (from.0 + m.0, from.1 + m.1);
-                (m.0 as usize, m.1 as usize)
-            })
-            .collect()
+    pub fn legal_moves(&self) -> Vec<Move> {
+        self.legal_moves_where(|_| true )
     }
 
-    pub fn path_is_clear(
-        &self,
-        from: &(usize, usize),
-        to: &(usize, usize)
-    ) -> bool {
+    // captures and promotions, the moves that change the material on the board
+    pub fn noisy_moves(&self) -> Vec<Move> {
 
-        self.find_path(from, to)
-         .iter()
-         .all(|(x, y)| self.board[*x][*y] == None )
+        self.legal_moves_where(|m| self.is_capture(m)
+            || matches!(m.piece, Piece::Pawn(_)) && [Rank::FIRST, Rank::EIGHTH].contains(&m.to.rank())
+        )
     }
 
-    pub fn find_king(
-        &self,
-        player: &Player
-    ) -> (usize, usize) {
-
-        (0..8)
-            .fold((0, 0), |mut acc, x| {
-                (0..8).for_each(|y| if self.board[x][y] == Some(Piece::King(*player)) {
-                    acc = (x, y);
-                });
-                acc
-            })
+    pub fn is_capture(&self, m: &Move) -> bool {
+
+        self.position.board[m.to].is_some()
+            || matches!(m.piece, Piece::Pawn(_)) && self.position.en_passant == Some(m.to)
     }
 
-    pub fn moves_left(
-        &self,
-        is_check: Option<Vec<Move>>
-    ) -> Option<Vec<Move>> {
+    // the piece a move takes, the pawn beside the destination for en passant
+    pub fn captured_piece(&self, m: &Move) -> Option<Piece> {
 
-        if let Some(checking_moves) = is_check {
-
-            let mut moves_left: Vec<Move> = Vec::new();
-
-            let player = self.turn.opponent();
-
-            Piece::King(player)
-                .possible_moves(&checking_moves[0].to)
-                .iter()
-                .for_each(|m| if m.is_valid_move(&mut self.clone(), Some(&player)).is_ok() {
-                    if self.is_check(&self.turn, Some(m)).is_none() {
-                        moves_left.push(*m)
-                    }
-                })
-            ;
-
-            checking_moves
-                .iter()
-                .for_each(|m| {
-
-                    let path = self.find_path(&m.from, &m.to);
-                    let checking_piece_tile = vec![m.from];
-
-                    let results = [
-                        self.piece_can_move_to(&player, path, true),
-                        self.piece_can_move_to(&player, checking_piece_tile, false)
-                    ];
-
-                    results
-                        .iter()
-                        .for_each(|r| {
-                            if let Some(moves) = r {
-                                moves
-                                    .iter()
-                                    .filter(|m| m.is_valid_move(&mut self.clone(), Some(&player)).is_ok() )
-                                    .for_each(|m| moves_left.push(*m) )
-                                ;
-                            }
-                        })
-                    ;
-                })
-            ;
-
-            Some(moves_left)
-        } else {
-            None
+        match self.position.board[m.to] {
+            Some(piece) => Some(piece),
+            None if self.is_capture(m) => self.position.board[Square::new(m.to.file(), m.from.rank())],
+            None => None
         }
     }
 
-    pub fn find_piece(
+    // only the moves that pass the filter are checked for legality
+    fn legal_moves_where<F: Fn(&Move) -> bool>(&self, keep: F) -> Vec<Move> {
+
+        Square::all()
+            .filter(|square| self.position.board[*square].is_some_and(|p| Player::find_player(&p) == self.position.turn ) )
+            .flat_map(|square| self.position.piece_moves(&square) )
+            .filter(|m| keep(m) )
+            .filter(|m| self.is_legal(m) )
+            .flat_map(|m| if MoveType::determine_type(self, &m) == MoveType::Promotion {
+                [
+                    Piece::Queen(self.position.turn),
+                    Piece::Rook(self.position.turn),
+                    Piece::Bishob(self.position.turn),
+                    Piece::Knight(self.position.turn)
+                ]
+                    .map(|p| Move {
+                        promotion: Some(p),
+                        ..m
+                    })
+                    .to_vec()
+            } else {
+                vec![m]
+            })
+            .collect()
+    }
+
+    pub fn legal_moves_from(
         &self,
-        tile: &(usize, usize)
-    ) -> Option<Piece> {
+        tile: &Square
+    ) -> Vec<Move> {
+
+        self.position
+            .piece_moves(tile)
+            .into_iter()
+            .filter(|m| Player::find_player(&m.piece) == self.position.turn && self.is_legal(m) )
+            .collect()
+    }
 
-        if let Some(piece) = self.board[tile.0][tile.1] {
+    // a move the piece may make is legal when it leaves its own king out of reach
+    fn is_legal(&self, m: &Move) -> bool {
 
-            let players = [Player::White, Player::Black];
+        let position = self.board_after(m);
 
-            players
-                .iter()
-                .fold(None, |mut acc, player|{
+        !position.is_attacked(&position.find_king(&self.position.turn), &self.position.turn.opponent())
+    }
 
-                    match Piece::get_pieces(player).iter().find(|p| *p == &piece ) {
-                        Some(p) => { acc = Some(*p); },
-                        None => ()
-                    }
+    // the pieces as the move leaves them, enough to look for checks
+    fn board_after(&self, m: &Move) -> Position {
 
-                    acc
-                })
-        } else {
-            None
+        let mut position = self.position;
+
+        // a pawn taken en passant stands beside the destination
+        if matches!(m.piece, Piece::Pawn(_)) && m.from.file() != m.to.file() && position.board[m.to].is_none() {
+            position.board[Square::new(m.to.file(), m.from.rank())] = None;
         }
+        position.board[m.from] = None;
+        position.board[m.to] = Some(m.piece);
+
+        position
     }
 }
\ No newline at end of file
@@ -0,0 +1,107 @@
+use crate::{
+    r#move::Move,
+    engine::is_mate_score
+};
+
+// a ladder of computer opponents, from one that blunders often to full strength
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Difficulty {
+    Beginner,
+    Casual,
+    Intermediate,
+    Advanced,
+    Master
+}
+
+// how the engine holds back at a difficulty level
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Skill {
+    pub depth: u32,
+    pub nodes: u64,
+    // up to this many centipawns are added to each move's score at random
+    pub noise: i32,
+    // the chance of playing any move that doesn't walk into a mate instead
+    pub blunder_chance: f64
+}
+
+impl Difficulty {
+    pub fn all() -> [Difficulty; 5] {
+
+        [
+            Difficulty::Beginner,
+            Difficulty::Casual,
+            Difficulty::Intermediate,
+            Difficulty::Advanced,
+            Difficulty::Master
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+
+        match self {
+            Difficulty::Beginner => "beginner",
+            Difficulty::Casual => "casual",
+            Difficulty::Intermediate => "intermediate",
+            Difficulty::Advanced => "advanced",
+            Difficulty::Master => "master"
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Difficulty> {
+
+        Difficulty::all()
+            .into_iter()
+            .find(|d| d.name().eq_ignore_ascii_case(name) )
+    }
+
+    // the master plays at full strength
+    pub fn skill(&self) -> Option<Skill> {
+
+        let (depth, nodes, noise, blunder_chance) = match self {
+            Difficulty::Beginner => (1, 1_000, 300, 0.2),
+            Difficulty::Casual => (2, 5_000, 120, 0.08),
+            Difficulty::Intermediate => (3, 20_000, 50, 0.03),
+            Difficulty::Advanced => (4, 100_000, 15, 0.0),
+            Difficulty::Master => return None
+        };
+
+        Some(Skill {
+            depth,
+            nodes,
+            noise,
+            blunder_chance
+        })
+    }
+}
+
+impl Skill {
+    // picks from the scored root moves; 'random' gives evenly spread numbers
+    pub fn pick<R: FnMut() -> u64>(
+        &self,
+        scores: &[(Move, i32)],
+        mut random: R
+    ) -> Option<(Move, i32)> {
+
+        let safe: Vec<(Move, i32)> = scores
+            .iter()
+            .filter(|(_, score)| !(is_mate_score(*score) && *score < 0) )
+            .copied()
+            .collect()
+        ;
+        let candidates = if safe.is_empty() { scores } else { &safe[..] };
+
+        if candidates.is_empty() {
+            return None
+        }
+
+        if (random() % 1000) as f64 / 1000.0 < self.blunder_chance {
+            return Some(candidates[random() as usize % candidates.len()])
+        }
+
+        candidates
+            .iter()
+            .map(|(m, score)| (*m, *score, *score + (random() % (self.noise as u64 + 1)) as i32) )
+            .max_by_key(|(_, _, noisy)| *noisy )
+            .map(|(m, score, _)| (m, score))
+    }
+}
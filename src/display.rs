@@ -3,50 +3,333 @@ use crate::{
     chess::Chess,
     piece::Piece,
     player::Player,
-    error::ChessError
+    r#move::Move,
+    eco::Opening,
+    game::{
+        Game,
+        GameResult
+    },
+    difficulty::Difficulty,
+    square::{
+        Square,
+        File,
+        Rank
+    },
+    error::{
+        ChessError,
+        Notation
+    },
+    constant::{
+        RESET,
+        LIGHT_TILE,
+        DARK_TILE,
+        LAST_MOVE_TILE,
+        CHECK_TILE,
+        DESTINATION_TILE,
+        CURSOR_TILE,
+        WHITE_PIECE,
+        BLACK_PIECE
+    }
 };
 
-impl fmt::Display for Chess {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Orientation {
+    #[default]
+    White,
+    Black,
+    Turn
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DisplayOptions {
+    pub unicode: bool,
+    pub colored: bool,
+    pub selected: Option<Square>,
+    pub cursor: Option<Square>,
+    pub orientation: Orientation
+}
+
+pub struct ChessDisplay<'a> {
+    chess: &'a Chess,
+    options: DisplayOptions,
+    last_move: Option<Move>,
+    captured: &'a [Piece]
+}
+
+struct Highlights {
+    last_move: Vec<Square>,
+    checked_king: Option<Square>,
+    destinations: Vec<Square>
+}
+
+impl Chess {
+    pub fn display(&self, options: DisplayOptions) -> ChessDisplay<'_> {
+
+        ChessDisplay {
+            chess: self,
+            options,
+            last_move: None,
+            captured: &[]
+        }
+    }
+}
+
+impl Game {
+    pub fn display(&self, options: DisplayOptions) -> ChessDisplay<'_> {
+
+        ChessDisplay {
+            chess: &self.chess,
+            options,
+            last_move: self.moves.last().copied(),
+            captured: &self.captured
+        }
+    }
+}
+
+impl ChessDisplay<'_> {
+    // the move to highlight on a colored board
+    pub fn with_last_move(self, m: &Move) -> Self {
+
+        ChessDisplay {
+            last_move: Some(*m),
+            ..self
+        }
+    }
+
+    pub fn perspective(&self) -> Player {
+
+        match self.options.orientation {
+            Orientation::White => Player::White,
+            Orientation::Black => Player::Black,
+            Orientation::Turn => self.chess.position.turn
+        }
+    }
+
+    pub fn tile_at(
+        &self,
+        line: usize,
+        column: usize
+    ) -> Option<Square> {
+
+        // line and column of a character counted from the top left corner of the board
+
+        if !(1..=8).contains(&line) || !(2..26).contains(&column) {
+            return None
+        }
 
-        let mut contents = String::from("   A  B  C  D  E  F  G  H\n");
+        let (row, col) = (line - 1, (column - 2) / 3);
 
-        let mut count: u8 = 0;
+        let (rank, file) = match self.perspective() {
+            Player::White => (7 - row, col),
+            Player::Black => (row, 7 - col)
+        };
 
-        let rest: String = self.board
+        Some(Square::new(File::new(file)?, Rank::new(rank)?))
+    }
+
+    // the arrow keys move the cursor the way the board is seen, staying put at its edges
+    pub fn step_cursor(
+        &self,
+        cursor: Square,
+        (up, right): (i8, i8)
+    ) -> Square {
+
+        let (up, right) = match self.perspective() {
+            Player::White => (up, right),
+            Player::Black => (-up, -right)
+        };
+
+        let cursor = cursor.offset((up, 0)).unwrap_or(cursor);
+
+        cursor.offset((0, right)).unwrap_or(cursor)
+    }
+
+    fn highlights(&self) -> Highlights {
+
+        let last_move = match self.last_move {
+            Some(m) => vec![m.from, m.to],
+            None => Vec::new()
+        };
+
+        let checked_king = [Player::White, Player::Black]
             .iter()
-            .flat_map(|x| x.iter().enumerate() )
-            .map(|(i, y)| {
-                    
-                let mut s = String::new();
-
-                let parse = |o: &Option<Piece>| match o {
-                    Some(piece) => format!("|{}", piece),
-                    None => String::from("|  ")
-                };
-                    
-                match i {
-                    0 => {
-                        count += 1;
-                        s.push_str(&format!("{} {}", count, parse(y)));
-                    },
-                    7 => match count {
-                        8 => s.push_str(&format!("{}|", parse(y))),
-                        _ => s.push_str(&format!("{}|\n", parse(y)))
-                    },
-                    _ => s.push_str(&parse(y))
-                }
-                s
-            })
+            .find(|p| self.chess.is_check(&p.opponent(), None).is_some() )
+            .map(|p| self.chess.position.find_king(p) )
+        ;
+
+        let destinations = match self.options.selected {
+            Some(tile) => self.chess
+                .legal_moves_from(&tile)
+                .iter()
+                .map(|m| m.to )
+                .collect(),
+            None => Vec::new()
+        };
+
+        Highlights {
+            last_move,
+            checked_king,
+            destinations
+        }
+    }
+
+    pub fn captured(&self, player: &Player) -> String {
+
+        let pieces: Vec<String> = self.captured
+            .iter()
+            .filter(|p| Player::find_player(p) != *player )
+            .map(|p| self.piece_code(p).trim_end().to_string() )
             .collect()
         ;
 
-        contents.push_str(&rest);
+        let balance = match player {
+            Player::White => self.chess.position.material_balance(),
+            Player::Black => -self.chess.position.material_balance()
+        };
+
+        if balance > 0 {
+            format!("{} +{}", pieces.join(" "), balance)
+        } else {
+            pieces.join(" ")
+        }
+    }
+
+    fn piece_code(&self, piece: &Piece) -> String {
+
+        if self.options.unicode {
+            format!("{} ", piece.unicode())
+        } else {
+            format!("{}", piece)
+        }
+    }
+
+    fn colored_tile(
+        &self,
+        tile: Square,
+        highlights: &Highlights
+    ) -> String {
+
+        let background = if self.options.cursor == Some(tile) {
+            CURSOR_TILE
+        } else if highlights.checked_king == Some(tile) {
+            CHECK_TILE
+        } else if self.options.selected == Some(tile) || highlights.destinations.contains(&tile) {
+            DESTINATION_TILE
+        } else if highlights.last_move.contains(&tile) {
+            LAST_MOVE_TILE
+        } else if (tile.rank().index() + tile.file().index()).is_multiple_of(2) {
+            DARK_TILE
+        } else {
+            LIGHT_TILE
+        };
+
+        match self.chess.position.board[tile] {
+            Some(piece) => {
+                let foreground = match Player::find_player(&piece) {
+                    Player::White => WHITE_PIECE,
+                    Player::Black => BLACK_PIECE
+                };
+                format!("{}{} {}", background, foreground, self.piece_code(&piece))
+            },
+            None => format!("{}   ", background)
+        }
+    }
+}
+
+impl fmt::Display for ChessDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+
+        let (ranks, files): (Vec<Rank>, Vec<File>) = match self.perspective() {
+            Player::White => (Rank::all().rev().collect(), File::all().collect()),
+            Player::Black => (Rank::all().collect(), File::all().rev().collect())
+        };
+
+        let mut contents: String = files
+            .iter()
+            .fold(String::from(" "), |acc, file| format!("{}  {}", acc, file) )
+        ;
+        contents.push('\n');
+
+        let rows: Vec<String> = if self.options.colored {
+
+            let highlights = self.highlights();
+
+            ranks
+                .iter()
+                .map(|rank| {
+                    let tiles: String = files
+                        .iter()
+                        .map(|file| self.colored_tile(Square::new(*file, *rank), &highlights) )
+                        .collect()
+                    ;
+                    format!("{} {}{}", rank, tiles, RESET)
+                })
+                .collect()
+        } else {
+
+            let parse = |o: &Option<Piece>| match o {
+                Some(piece) => format!("|{}", self.piece_code(piece)),
+                None => String::from("|  ")
+            };
+
+            ranks
+                .iter()
+                .map(|rank| {
+                    let tiles: String = files
+                        .iter()
+                        .map(|file| parse(&self.chess.position.board[Square::new(*file, *rank)]) )
+                        .collect()
+                    ;
+                    format!("{} {}|", rank, tiles)
+                })
+                .collect()
+        };
+
+        contents.push_str(&rows.join("\n"));
 
         write!(f, "{}", contents)
     }
 }
 
+impl fmt::Display for Chess {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.display(DisplayOptions::default()))
+    }
+}
+
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+
+        let result = match self {
+            GameResult::WhiteWon => "1-0",
+            GameResult::BlackWon => "0-1",
+            GameResult::Draw => "1/2-1/2",
+            GameResult::Unfinished => "*"
+        };
+
+        write!(f, "{}", result)
+    }
+}
+
+impl Piece {
+    pub fn unicode(&self) -> char {
+        match self {
+            Piece::Pawn(Player::White) => '♙',
+            Piece::Bishob(Player::White) => '♗',
+            Piece::Knight(Player::White) => '♘',
+            Piece::Rook(Player::White) => '♖',
+            Piece::Queen(Player::White) => '♕',
+            Piece::King(Player::White) => '♔',
+            Piece::Pawn(Player::Black) => '♟',
+            Piece::Bishob(Player::Black) => '♝',
+            Piece::Knight(Player::Black) => '♞',
+            Piece::Rook(Player::Black) => '♜',
+            Piece::Queen(Player::Black) => '♛',
+            Piece::King(Player::Black) => '♚'
+        }
+    }
+}
+
 impl fmt::Display for Piece {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -66,6 +349,46 @@ impl fmt::Display for Piece {
     }
 }
 
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+
+        write!(f, "{}-{}", self.from, self.to)
+    }
+}
+
+impl fmt::Display for Opening {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.eco, self.name)
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+
+        let name = self.name();
+
+        write!(f, "{}{}", name[..1].to_uppercase(), &name[1..])
+    }
+}
+
+impl fmt::Display for File {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", (b'A' + self.index() as u8) as char)
+    }
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.index() + 1)
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.file(), self.rank())
+    }
+}
+
 impl fmt::Display for Player {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -75,19 +398,75 @@ impl fmt::Display for Player {
     }
 }
 
+impl fmt::Display for Notation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Notation::Move => write!(f, "move"),
+            Notation::Square => write!(f, "square"),
+            Notation::Fen => write!(f, "FEN"),
+            Notation::Pgn => write!(f, "PGN")
+        }
+    }
+}
+
 impl fmt::Display for ChessError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            ChessError::UnableToParseInput => write!(f, "Can't understand input."),
-            ChessError::PieceBelongsToOpponent => write!(f, "That is opponent's piece."),
-            ChessError::NotAllowedMove => write!(f, "Not an allowed move."),
-            ChessError::NotAllowedMoveInCheck => write!(f, "Not an allowed move; you are in a check."),
-            ChessError::EmptyTile => write!(f, "Empty tile chosen."),
-            ChessError::InvalidDestination => write!(f, "Can't move there."),
-            ChessError::PathIsBlocked => write!(f, "Can't move there; path is blocked."),
-            ChessError::KingCompromised => write!(f, "Can't move that piece; your king would compromised."),
-            ChessError::CastlingMoveUnavailable => write!(f, "Castling move is no longer available."),
-            ChessError::CastlingPathIsCompromised => write!(f, "Castling is prevented by opponent's piece.")
+            ChessError::UnableToParseInput {
+                notation,
+                offset,
+                found
+            } => if found.is_empty() {
+                write!(f, "Can't understand {}; input ends unexpectedly at offset {}.", notation, offset)
+            } else {
+                write!(f, "Can't understand {}; unexpected '{}' at offset {}.", notation, found, offset)
+            },
+            ChessError::PieceBelongsToOpponent {
+                tile
+            } => write!(f, "That is opponent's piece on {}.", tile),
+            ChessError::NotAllowedMove {
+                attempted
+            } => write!(f, "Not an allowed move: {}.", attempted),
+            ChessError::NotAllowedMoveInCheck {
+                attempted
+            } => write!(f, "Not an allowed move: {}; you are in a check.", attempted),
+            ChessError::EmptyTile {
+                tile
+            } => write!(f, "Empty tile chosen: {}.", tile),
+            ChessError::InvalidDestination {
+                tile
+            } => write!(f, "Can't move there: {}.", tile),
+            ChessError::PathIsBlocked {
+                blocker,
+                tile
+            } => write!(f, "Can't move there; path is blocked by {} on {}.", blocker, tile),
+            ChessError::KingCompromised {
+                attacker,
+                tile
+            } => write!(f, "Can't move that piece; your king would be compromised by {} on {}.", attacker, tile),
+            ChessError::CastlingMoveUnavailable {
+                rook,
+                lost_by
+            } => match lost_by {
+                Some(Move { piece: Piece::King(_), .. }) => write!(f, "Castling move is no longer available; your king has moved."),
+                Some(m) if m.from == *rook => write!(f, "Castling move is no longer available; the rook on {} has moved.", rook),
+                Some(_) => write!(f, "Castling move is no longer available; the rook on {} was captured.", rook),
+                None => write!(f, "Castling move is no longer available on that side.")
+            },
+            ChessError::CastlingPathIsCompromised {
+                attacker,
+                from,
+                tile
+            } => write!(
+                f,
+                "Castling is prevented by opponent's {} on {} attacking {}.",
+                attacker,
+                from,
+                tile
+            ),
+            ChessError::InvalidPromotion {
+                piece
+            } => write!(f, "Can't promote to {}; choose a queen, rook, bishop or knight.", piece)
         }
     }
 }
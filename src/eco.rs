@@ -0,0 +1,131 @@
+use crate::{
+    game::Game,
+    position::Position
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Opening {
+    pub eco: &'static str,
+    pub name: &'static str
+}
+
+// ECO code, name and the moves that define the opening in SAN
+static OPENINGS: &[(&str, &str, &str)] = &[
+    ("A01", "Nimzo-Larsen Attack", "b3"),
+    ("A02", "Bird's Opening", "f4"),
+    ("A04", "Reti Opening", "Nf3"),
+    ("A06", "Reti Opening", "Nf3 d5"),
+    ("A10", "English Opening", "c4"),
+    ("A13", "English Opening", "c4 e6"),
+    ("A15", "English Opening: Anglo-Indian Defence", "c4 Nf6"),
+    ("A20", "English Opening: King's English Variation", "c4 e5"),
+    ("A30", "English Opening: Symmetrical Variation", "c4 c5"),
+    ("A40", "Queen's Pawn Game", "d4"),
+    ("A43", "Old Benoni Defence", "d4 c5"),
+    ("A45", "Indian Defence", "d4 Nf6"),
+    ("A46", "Indian Defence", "d4 Nf6 Nf3"),
+    ("A51", "Budapest Gambit", "d4 Nf6 c4 e5"),
+    ("A56", "Benoni Defence", "d4 Nf6 c4 c5"),
+    ("A57", "Benko Gambit", "d4 Nf6 c4 c5 d5 b5"),
+    ("A80", "Dutch Defence", "d4 f5"),
+    ("B00", "King's Pawn Opening", "e4"),
+    ("B01", "Scandinavian Defence", "e4 d5"),
+    ("B02", "Alekhine's Defence", "e4 Nf6"),
+    ("B06", "Modern Defence", "e4 g6"),
+    ("B07", "Pirc Defence", "e4 d6 d4 Nf6"),
+    ("B10", "Caro-Kann Defence", "e4 c6"),
+    ("B12", "Caro-Kann Defence: Advance Variation", "e4 c6 d4 d5 e5"),
+    ("B13", "Caro-Kann Defence: Exchange Variation", "e4 c6 d4 d5 exd5 cxd5"),
+    ("B15", "Caro-Kann Defence", "e4 c6 d4 d5 Nc3"),
+    ("B20", "Sicilian Defence", "e4 c5"),
+    ("B21", "Sicilian Defence: Smith-Morra Gambit", "e4 c5 d4 cxd4 c3"),
+    ("B22", "Sicilian Defence: Alapin Variation", "e4 c5 c3"),
+    ("B23", "Sicilian Defence: Closed", "e4 c5 Nc3"),
+    ("B27", "Sicilian Defence", "e4 c5 Nf3"),
+    ("B30", "Sicilian Defence", "e4 c5 Nf3 Nc6"),
+    ("B40", "Sicilian Defence: French Variation", "e4 c5 Nf3 e6"),
+    ("B50", "Sicilian Defence", "e4 c5 Nf3 d6"),
+    ("B54", "Sicilian Defence", "e4 c5 Nf3 d6 d4 cxd4 Nxd4"),
+    ("B70", "Sicilian Defence: Dragon Variation", "e4 c5 Nf3 d6 d4 cxd4 Nxd4 Nf6 Nc3 g6"),
+    ("B90", "Sicilian Defence: Najdorf Variation", "e4 c5 Nf3 d6 d4 cxd4 Nxd4 Nf6 Nc3 a6"),
+    ("C00", "French Defence", "e4 e6"),
+    ("C01", "French Defence: Exchange Variation", "e4 e6 d4 d5 exd5"),
+    ("C02", "French Defence: Advance Variation", "e4 e6 d4 d5 e5"),
+    ("C03", "French Defence: Tarrasch Variation", "e4 e6 d4 d5 Nd2"),
+    ("C10", "French Defence: Paulsen Variation", "e4 e6 d4 d5 Nc3"),
+    ("C11", "French Defence: Classical Variation", "e4 e6 d4 d5 Nc3 Nf6"),
+    ("C15", "French Defence: Winawer Variation", "e4 e6 d4 d5 Nc3 Bb4"),
+    ("C20", "King's Pawn Game", "e4 e5"),
+    ("C21", "Center Game", "e4 e5 d4 exd4"),
+    ("C23", "Bishop's Opening", "e4 e5 Bc4"),
+    ("C25", "Vienna Game", "e4 e5 Nc3"),
+    ("C30", "King's Gambit", "e4 e5 f4"),
+    ("C33", "King's Gambit Accepted", "e4 e5 f4 exf4"),
+    ("C40", "King's Knight Opening", "e4 e5 Nf3"),
+    ("C41", "Philidor Defence", "e4 e5 Nf3 d6"),
+    ("C42", "Petrov's Defence", "e4 e5 Nf3 Nf6"),
+    ("C44", "King's Knight Opening: Normal Variation", "e4 e5 Nf3 Nc6"),
+    ("C44", "Scotch Game", "e4 e5 Nf3 Nc6 d4"),
+    ("C45", "Scotch Game", "e4 e5 Nf3 Nc6 d4 exd4 Nxd4"),
+    ("C46", "Three Knights Opening", "e4 e5 Nf3 Nc6 Nc3"),
+    ("C47", "Four Knights Game", "e4 e5 Nf3 Nc6 Nc3 Nf6"),
+    ("C50", "Italian Game", "e4 e5 Nf3 Nc6 Bc4"),
+    ("C50", "Italian Game: Giuoco Piano", "e4 e5 Nf3 Nc6 Bc4 Bc5"),
+    ("C51", "Italian Game: Evans Gambit", "e4 e5 Nf3 Nc6 Bc4 Bc5 b4"),
+    ("C53", "Italian Game: Classical Variation", "e4 e5 Nf3 Nc6 Bc4 Bc5 c3"),
+    ("C55", "Italian Game: Two Knights Defence", "e4 e5 Nf3 Nc6 Bc4 Nf6"),
+    ("C60", "Ruy Lopez", "e4 e5 Nf3 Nc6 Bb5"),
+    ("C65", "Ruy Lopez: Berlin Defence", "e4 e5 Nf3 Nc6 Bb5 Nf6"),
+    ("C68", "Ruy Lopez: Exchange Variation", "e4 e5 Nf3 Nc6 Bb5 a6 Bxc6"),
+    ("C70", "Ruy Lopez: Morphy Defence", "e4 e5 Nf3 Nc6 Bb5 a6 Ba4"),
+    ("C78", "Ruy Lopez: Morphy Defence", "e4 e5 Nf3 Nc6 Bb5 a6 Ba4 Nf6 O-O"),
+    ("C84", "Ruy Lopez: Closed", "e4 e5 Nf3 Nc6 Bb5 a6 Ba4 Nf6 O-O Be7"),
+    ("D00", "Queen's Pawn Game", "d4 d5"),
+    ("D02", "Queen's Pawn Game", "d4 d5 Nf3"),
+    ("D06", "Queen's Gambit", "d4 d5 c4"),
+    ("D07", "Queen's Gambit Declined: Chigorin Defence", "d4 d5 c4 Nc6"),
+    ("D08", "Queen's Gambit Declined: Albin Countergambit", "d4 d5 c4 e5"),
+    ("D10", "Slav Defence", "d4 d5 c4 c6"),
+    ("D20", "Queen's Gambit Accepted", "d4 d5 c4 dxc4"),
+    ("D30", "Queen's Gambit Declined", "d4 d5 c4 e6"),
+    ("D35", "Queen's Gambit Declined", "d4 d5 c4 e6 Nc3 Nf6"),
+    ("D43", "Semi-Slav Defence", "d4 d5 c4 c6 Nf3 Nf6 Nc3 e6"),
+    ("D80", "Grunfeld Defence", "d4 Nf6 c4 g6 Nc3 d5"),
+    ("E00", "Indian Defence", "d4 Nf6 c4 e6"),
+    ("E01", "Catalan Opening", "d4 Nf6 c4 e6 g3"),
+    ("E10", "Indian Defence", "d4 Nf6 c4 e6 Nf3"),
+    ("E12", "Queen's Indian Defence", "d4 Nf6 c4 e6 Nf3 b6"),
+    ("E20", "Nimzo-Indian Defence", "d4 Nf6 c4 e6 Nc3 Bb4"),
+    ("E60", "King's Indian Defence", "d4 Nf6 c4 g6"),
+    ("E61", "King's Indian Defence", "d4 Nf6 c4 g6 Nc3"),
+    ("E70", "King's Indian Defence", "d4 Nf6 c4 g6 Nc3 Bg7 e4")
+];
+
+impl Game {
+    // the most specific opening whose moves start the game
+    pub fn opening(&self) -> Option<Opening> {
+
+        // the openings are told apart by their moves from the usual setup
+        if self.start != Position::new() {
+            return None
+        }
+
+        let played: Vec<&str> = self.san
+            .iter()
+            .map(|san| san.trim_end_matches(['+', '#']) )
+            .collect()
+        ;
+
+        OPENINGS
+            .iter()
+            .filter(|(_, _, moves)| {
+                let moves: Vec<&str> = moves.split_whitespace().collect();
+                moves.len() <= played.len() && moves.iter().zip(&played).all(|(a, b)| a == b )
+            })
+            .max_by_key(|(_, _, moves)| moves.split_whitespace().count() )
+            .map(|(eco, name, _)| Opening {
+                eco,
+                name
+            })
+    }
+}
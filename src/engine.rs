@@ -0,0 +1,644 @@
+use std::{
+    cmp::Reverse,
+    sync::{
+        atomic::{
+            AtomicBool,
+            AtomicU64,
+            Ordering
+        },
+        Arc
+    },
+    thread,
+    time::{
+        Duration,
+        Instant,
+        SystemTime,
+        UNIX_EPOCH
+    }
+};
+use crate::{
+    chess::{
+        Chess,
+        ChessState
+    },
+    r#move::Move,
+    player::Player,
+    eval::EvalParams,
+    book::Book,
+    difficulty::{
+        Difficulty,
+        Skill
+    },
+    tt::{
+        Bound,
+        TranspositionTable,
+        TtEntry
+    }
+};
+
+pub const MATE: i32 = 100_000;
+const INFINITY: i32 = 1_000_000;
+const MAX_DEPTH: u32 = 64;
+pub const MAX_THREADS: usize = 256;
+
+// kept back from the clock for reading the move and playing it
+const MOVE_OVERHEAD: Duration = Duration::from_millis(50);
+// moves the remaining time is shared between when the time control doesn't say
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeControl {
+    pub remaining: Duration,
+    pub increment: Duration,
+    pub moves_to_go: Option<u32>
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SearchLimits {
+    pub depth: Option<u32>,
+    // counted per thread, and only once the first iteration is done
+    pub nodes: Option<u64>,
+    pub move_time: Option<Duration>,
+    pub clock: Option<TimeControl>
+}
+
+// no new iteration is started after the soft limit and the search is cut off at the hard limit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeBudget {
+    pub soft: Duration,
+    pub hard: Duration
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchResult {
+    pub best_move: Option<Move>,
+    pub score: i32,
+    pub depth: u32,
+    pub nodes: u64
+}
+
+pub struct Engine {
+    stop: Arc<AtomicBool>,
+    tt: TranspositionTable,
+    threads: usize,
+    params: EvalParams,
+    skill: Option<Skill>,
+    book: Option<Book>,
+    random: AtomicU64
+}
+
+struct Search<'a> {
+    stop: &'a AtomicBool,
+    // set once the main thread is done, to end the helper threads
+    finished: &'a AtomicBool,
+    tt: &'a TranspositionTable,
+    params: &'a EvalParams,
+    deadline: Option<Instant>,
+    max_nodes: Option<u64>,
+    nodes: u64,
+    // the deepest iteration finished so far
+    completed: u32,
+    aborted: bool,
+    // every root move is searched with a full window, for a handicapped engine to choose from
+    exact_root: bool,
+    root_scores: Vec<(Move, i32)>
+}
+
+impl TimeBudget {
+    pub fn allocate(control: &TimeControl) -> TimeBudget {
+
+        let available = control.remaining.saturating_sub(MOVE_OVERHEAD);
+        let moves_to_go = control.moves_to_go.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+
+        let soft = (available / moves_to_go + control.increment * 3 / 4).min(available);
+
+        TimeBudget {
+            soft,
+            hard: (soft * 4).min(available)
+        }
+    }
+
+    pub fn from_limits(limits: &SearchLimits) -> Option<TimeBudget> {
+
+        match (limits.move_time, &limits.clock) {
+            (Some(move_time), _) => Some(TimeBudget {
+                soft: move_time,
+                hard: move_time
+            }),
+            (None, Some(control)) => Some(TimeBudget::allocate(control)),
+            (None, None) => None
+        }
+    }
+}
+
+impl SearchResult {
+    pub fn is_mate(&self) -> bool {
+        is_mate_score(self.score)
+    }
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Engine {
+    pub fn new() -> Engine {
+
+        Engine {
+            stop: Arc::default(),
+            tt: TranspositionTable::default(),
+            threads: 1,
+            params: EvalParams::default(),
+            skill: None,
+            book: None,
+            random: AtomicU64::new(SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_nanos() as u64 | 1 )
+                .unwrap_or(1)
+            )
+        }
+    }
+
+    pub fn with_hash_size(size_mb: usize) -> Engine {
+
+        Engine {
+            tt: TranspositionTable::new(size_mb),
+            ..Engine::new()
+        }
+    }
+
+    pub fn set_hash_size(&mut self, size_mb: usize) {
+        self.tt = TranspositionTable::new(size_mb);
+    }
+
+    pub fn params(&self) -> &EvalParams {
+        &self.params
+    }
+
+    pub fn set_params(&mut self, params: EvalParams) {
+        self.params = params;
+    }
+
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.skill = difficulty.skill();
+    }
+
+    // positions in the book are answered with one of its moves instead of a search
+    pub fn set_book(&mut self, book: Option<Book>) {
+        self.book = book;
+    }
+
+    // the same seed makes a handicapped engine pick the same moves again
+    pub fn set_seed(&self, seed: u64) {
+        self.random.store(seed | 1, Ordering::Relaxed);
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    // the threads search the same position and share what they find through the table
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.clamp(1, MAX_THREADS);
+    }
+
+    // forgets what earlier searches found, e.g. for a new game
+    pub fn clear_hash(&self) {
+        self.tt.clear();
+    }
+
+    // setting the flag from another thread ends the search in progress
+    pub fn stop_handle(&self) -> Arc<AtomicBool> {
+        self.stop.clone()
+    }
+
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    pub fn search(
+        &self,
+        chess: &Chess,
+        limits: &SearchLimits
+    ) -> SearchResult {
+
+        self.stop.store(false, Ordering::Relaxed);
+
+        self.search_with_stop(chess, limits, &self.stop)
+    }
+
+    // the caller's flag ends the search, so a stop given before the search starts isn't lost
+    pub fn search_with_stop(
+        &self,
+        chess: &Chess,
+        limits: &SearchLimits,
+        stop: &AtomicBool
+    ) -> SearchResult {
+
+        let start = Instant::now();
+        let budget = TimeBudget::from_limits(limits);
+        let max_depth = limits.depth
+            .unwrap_or(MAX_DEPTH)
+            .min(self.skill.map(|s| s.depth ).unwrap_or(MAX_DEPTH))
+            .clamp(1, MAX_DEPTH)
+        ;
+        let max_nodes = match (limits.nodes, self.skill.map(|s| s.nodes )) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b)
+        };
+        let finished = AtomicBool::new(false);
+
+        let book_move = self.book
+            .as_ref()
+            .and_then(|book| book.pick_with(chess, self.next_random()) )
+        ;
+        if book_move.is_some() {
+            return SearchResult {
+                best_move: book_move,
+                score: 0,
+                depth: 0,
+                nodes: 0
+            }
+        }
+
+        let mut moves = chess.legal_moves();
+        let tt_move = self.tt.probe(chess.position.key()).and_then(|e| e.best_move );
+        order_moves(chess, &mut moves, tt_move);
+
+        // nothing to think about with a single legal move
+        if moves.len() < 2 {
+            return SearchResult {
+                best_move: moves.first().copied(),
+                score: 0,
+                depth: 0,
+                nodes: 0
+            }
+        }
+
+        let search = |id: usize| {
+            let mut search = Search {
+                stop,
+                finished: &finished,
+                tt: &self.tt,
+                params: &self.params,
+                deadline: budget.map(|b| start + b.hard ),
+                max_nodes,
+                nodes: 0,
+                completed: 0,
+                aborted: false,
+                exact_root: self.skill.is_some(),
+                root_scores: Vec::new()
+            };
+            // every other helper starts a depth deeper, so the threads don't all search alike
+            let result = search.iterate(chess, moves.clone(), 1 + id as u32 % 2, max_depth, budget.map(|b| start + b.soft ));
+            (result, search.root_scores)
+        };
+
+        thread::scope(|scope| {
+
+            let search = &search;
+            let helpers: Vec<_> = (1..self.threads)
+                .map(|id| scope.spawn(move || search(id) ) )
+                .collect()
+            ;
+
+            let (mut result, scores) = search(0);
+            finished.store(true, Ordering::Relaxed);
+
+            result.nodes += helpers
+                .into_iter()
+                .map(|helper| helper.join().map(|(r, _)| r.nodes ).unwrap_or(0) )
+                .sum::<u64>()
+            ;
+
+            if let Some((m, score)) = self.skill.and_then(|skill| skill.pick(&scores, || self.next_random() )) {
+                result.best_move = Some(m);
+                result.score = score;
+            }
+
+            result
+        })
+    }
+
+    // xorshift, which is plenty for choosing a weaker move now and then
+    fn next_random(&self) -> u64 {
+
+        let mut x = self.random.load(Ordering::Relaxed);
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.random.store(x, Ordering::Relaxed);
+
+        x
+    }
+}
+
+
+impl Search<'_> {
+    fn iterate(
+        &mut self,
+        chess: &Chess,
+        mut moves: Vec<Move>,
+        first_depth: u32,
+        max_depth: u32,
+        soft_deadline: Option<Instant>
+    ) -> SearchResult {
+
+        let mut result = SearchResult {
+            best_move: moves.first().copied(),
+            score: 0,
+            depth: 0,
+            nodes: 0
+        };
+
+        for depth in first_depth.min(max_depth)..=max_depth {
+
+            let (best, score, scores) = self.root(chess, &moves, depth);
+
+            // an unfinished iteration is thrown away
+            if self.aborted {
+                break
+            }
+
+            self.completed = depth;
+            self.root_scores = scores;
+
+            result = SearchResult {
+                best_move: Some(moves[best]),
+                score,
+                depth,
+                nodes: self.nodes
+            };
+
+            // the best move is searched first in the next iteration
+            moves[..=best].rotate_right(1);
+            self.store(chess, depth, 0, Bound::Exact, score, Some(moves[0]));
+
+            if result.is_mate()
+                || soft_deadline.is_some_and(|d| Instant::now() >= d )
+                || self.max_nodes.is_some_and(|n| self.nodes >= n ) {
+                break
+            }
+        }
+
+        result.nodes = self.nodes;
+
+        result
+    }
+
+    fn should_stop(&mut self) -> bool {
+
+        if !self.aborted {
+            self.aborted = self.stop.load(Ordering::Relaxed)
+                || self.finished.load(Ordering::Relaxed)
+                || self.deadline.is_some_and(|d| Instant::now() >= d )
+                || self.completed > 0 && self.max_nodes.is_some_and(|n| self.nodes >= n );
+        }
+
+        self.aborted
+    }
+
+    // the score from the side to move's point of view
+    fn evaluate(&self, chess: &Chess) -> i32 {
+
+        let score = self.params.evaluate(&chess.position);
+
+        match chess.position.turn {
+            Player::White => score,
+            Player::Black => -score
+        }
+    }
+
+    fn store(
+        &self,
+        chess: &Chess,
+        depth: u32,
+        ply: i32,
+        bound: Bound,
+        score: i32,
+        best_move: Option<Move>
+    ) {
+
+        self.tt.store(TtEntry {
+            key: chess.position.key(),
+            depth,
+            bound,
+            score: score_to_tt(score, ply),
+            best_move
+        });
+    }
+
+    fn root(
+        &mut self,
+        chess: &Chess,
+        moves: &[Move],
+        depth: u32
+    ) -> (usize, i32, Vec<(Move, i32)>) {
+
+        let mut best = (0, -INFINITY);
+        let mut scores = Vec::new();
+
+        for (i, m) in moves.iter().enumerate() {
+
+            let alpha = if self.exact_root { -INFINITY } else { best.1 };
+            let score = self.child(chess, m, depth, 0, alpha, INFINITY);
+
+            if self.aborted {
+                break
+            }
+            if self.exact_root {
+                scores.push((*m, score));
+            }
+            if score > best.1 {
+                best = (i, score);
+            }
+        }
+
+        (best.0, best.1, scores)
+    }
+
+    // the score of a move from the mover's point of view
+    fn child(
+        &mut self,
+        chess: &Chess,
+        m: &Move,
+        depth: u32,
+        ply: i32,
+        alpha: i32,
+        beta: i32
+    ) -> i32 {
+
+        let mut child = chess.clone();
+
+        match child.make_move(m) {
+            Ok(ChessState::Checkmate) => MATE - (ply + 1),
+            Ok(_) => -self.negamax(&child, depth - 1, ply + 1, -beta, -alpha),
+            Err(_) => -INFINITY
+        }
+    }
+
+    fn negamax(
+        &mut self,
+        chess: &Chess,
+        depth: u32,
+        ply: i32,
+        mut alpha: i32,
+        beta: i32
+    ) -> i32 {
+
+        if depth == 0 {
+            return self.quiescence(chess, ply, alpha, beta)
+        }
+
+        self.nodes += 1;
+
+        if self.should_stop() {
+            return 0
+        }
+
+        let entry = self.tt.probe(chess.position.key());
+
+        if let Some(entry) = entry.filter(|e| e.depth >= depth ) {
+            let score = score_from_tt(entry.score, ply);
+            match entry.bound {
+                Bound::Exact => return score,
+                Bound::Lower if score >= beta => return score,
+                Bound::Upper if score <= alpha => return score,
+                _ => ()
+            }
+        }
+
+        let mut moves = chess.legal_moves();
+
+        if moves.is_empty() {
+            return 0 // stalemate, as checkmates are scored when the move is made
+        }
+
+        order_moves(chess, &mut moves, entry.and_then(|e| e.best_move ));
+
+        let mut best_move = None;
+
+        for m in &moves {
+
+            let score = self.child(chess, m, depth, ply, alpha, beta);
+
+            if self.aborted {
+                return 0
+            }
+            if score >= beta {
+                self.store(chess, depth, ply, Bound::Lower, beta, Some(*m));
+                return beta
+            }
+            if score > alpha {
+                alpha = score;
+                best_move = Some(*m);
+            }
+        }
+
+        match best_move {
+            Some(_) => self.store(chess, depth, ply, Bound::Exact, alpha, best_move),
+            None => self.store(chess, depth, ply, Bound::Upper, alpha, None)
+        }
+
+        alpha
+    }
+
+    // captures and promotions are played out so that no exchange is cut off halfway
+    fn quiescence(
+        &mut self,
+        chess: &Chess,
+        ply: i32,
+        mut alpha: i32,
+        beta: i32
+    ) -> i32 {
+
+        self.nodes += 1;
+
+        if self.should_stop() {
+            return 0
+        }
+
+        // the side to move may also stop capturing, but a check has to be answered
+        let mut moves: Vec<Move> = if let ChessState::Check { moves_left, .. } = &chess.state {
+            moves_left.clone()
+        } else {
+
+            let stand_pat = self.evaluate(chess);
+
+            if stand_pat >= beta {
+                return beta
+            }
+            alpha = alpha.max(stand_pat);
+
+            chess
+                .noisy_moves()
+                .into_iter()
+                .filter(|m| chess.see(m) >= 0 )
+                .collect()
+        };
+        order_moves(chess, &mut moves, None);
+
+        for m in &moves {
+
+            let mut child = chess.clone();
+
+            let score = match child.make_move(m) {
+                Ok(ChessState::Checkmate) => MATE - (ply + 1),
+                Ok(_) => -self.quiescence(&child, ply + 1, -beta, -alpha),
+                Err(_) => continue
+            };
+
+            if self.aborted {
+                return 0
+            }
+            if score >= beta {
+                return beta
+            }
+            alpha = alpha.max(score);
+        }
+
+        alpha
+    }
+}
+
+pub fn is_mate_score(score: i32) -> bool {
+    score.abs() >= MATE - MAX_DEPTH as i32
+}
+
+// mate scores are stored as distances from the position rather than from the root
+fn score_to_tt(score: i32, ply: i32) -> i32 {
+
+    if score >= MATE - MAX_DEPTH as i32 {
+        score + ply
+    } else if score <= -MATE + MAX_DEPTH as i32 {
+        score - ply
+    } else {
+        score
+    }
+}
+
+fn score_from_tt(score: i32, ply: i32) -> i32 {
+
+    if score >= MATE - MAX_DEPTH as i32 {
+        score - ply
+    } else if score <= -MATE + MAX_DEPTH as i32 {
+        score + ply
+    } else {
+        score
+    }
+}
+
+// the move the table remembers comes first, then captures of the most valuable pieces
+fn order_moves(
+    chess: &Chess,
+    moves: &mut [Move],
+    tt_move: Option<Move>
+) {
+
+    moves.sort_by_key(|m| (
+        Some(*m) != tt_move,
+        Reverse(chess.position.board[m.to].map(|p| p.value() ).unwrap_or(0))
+    ));
+}
@@ -1,13 +1,60 @@
+use std::error::Error;
+use crate::{
+    piece::Piece,
+    r#move::Move,
+    square::Square
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Notation {
+    Move,
+    Square,
+    Fen,
+    Pgn
+}
+
 #[derive(Debug, PartialEq)]
 pub enum ChessError {
-    UnableToParseInput,
-    PieceBelongsToOpponent,
-    NotAllowedMove,
-    NotAllowedMoveInCheck,
-    EmptyTile,
-    InvalidDestination,
-    PathIsBlocked,
-    KingCompromised,
-    CastlingMoveUnavailable,
-    CastlingPathIsCompromised
+    UnableToParseInput {
+        notation: Notation,
+        offset: usize,
+        found: String
+    },
+    PieceBelongsToOpponent {
+        tile: Square
+    },
+    NotAllowedMove {
+        attempted: Move
+    },
+    NotAllowedMoveInCheck {
+        attempted: Move
+    },
+    EmptyTile {
+        tile: Square
+    },
+    InvalidDestination {
+        tile: Square
+    },
+    PathIsBlocked {
+        blocker: Piece,
+        tile: Square
+    },
+    KingCompromised {
+        attacker: Piece,
+        tile: Square
+    },
+    CastlingMoveUnavailable {
+        rook: Square,
+        lost_by: Option<Move>
+    },
+    CastlingPathIsCompromised {
+        attacker: Piece,
+        from: Square,
+        tile: Square
+    },
+    InvalidPromotion {
+        piece: Piece
+    }
 }
+
+impl Error for ChessError {}
@@ -0,0 +1,278 @@
+#[cfg(feature = "serde")]
+use std::{
+    fs,
+    io,
+    path::Path
+};
+use crate::{
+    piece::Piece,
+    player::Player,
+    position::Position,
+    square::{
+        Square,
+        Rank
+    },
+    constant::{
+        DIAGONALS,
+        STRAIGHTS,
+        PAWN_SQUARES,
+        KNIGHT_SQUARES,
+        BISHOP_SQUARES,
+        ROOK_SQUARES,
+        QUEEN_SQUARES,
+        KING_SQUARES
+    }
+};
+
+const PAWN: usize = 0;
+const KING: usize = 5;
+
+// everything the evaluation weighs, in centipawns; pieces are listed as
+// pawn, knight, bishop, rook, queen and king
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EvalParams {
+    // the king has no material value
+    pub material: [i32; 5],
+    // 64 squares per piece, laid out as the board is printed with White at the bottom
+    pub piece_squares: [Vec<i32>; 6],
+    // per square a knight, bishop, rook or queen can move to
+    pub mobility: [i32; 4],
+    // per own pawn on the three squares in front of the king
+    pub king_shelter: i32,
+    // per square next to the king the opponent attacks
+    pub king_attack: i32,
+    pub doubled_pawn: i32,
+    pub isolated_pawn: i32,
+    // by the rank a pawn with no opposing pawns ahead has reached, counted from its own side
+    pub passed_pawn: [i32; 8]
+}
+
+impl Default for EvalParams {
+    fn default() -> Self {
+
+        EvalParams {
+            material: [100, 320, 330, 500, 900],
+            piece_squares: [
+                PAWN_SQUARES.to_vec(),
+                KNIGHT_SQUARES.to_vec(),
+                BISHOP_SQUARES.to_vec(),
+                ROOK_SQUARES.to_vec(),
+                QUEEN_SQUARES.to_vec(),
+                KING_SQUARES.to_vec()
+            ],
+            mobility: [4, 5, 2, 1],
+            king_shelter: 10,
+            king_attack: -8,
+            doubled_pawn: -10,
+            isolated_pawn: -15,
+            passed_pawn: [0, 5, 10, 20, 35, 60, 100, 0]
+        }
+    }
+}
+
+impl EvalParams {
+    #[cfg(feature = "serde")]
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<EvalParams> {
+
+        let json = fs::read_to_string(path)?;
+        let params: EvalParams = serde_json::from_str(&json)?;
+
+        if params.piece_squares.iter().any(|table| table.len() != 64 ) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "piece-square tables must have 64 squares"))
+        }
+
+        Ok(params)
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+
+        let json = serde_json::to_string_pretty(self)?;
+
+        fs::write(path, json)
+    }
+
+    // every parameter in a fixed order, for the tuner to adjust one at a time
+    pub fn values_mut(&mut self) -> Vec<&mut i32> {
+
+        self.material
+            .iter_mut()
+            .chain(self.piece_squares.iter_mut().flatten())
+            .chain(self.mobility.iter_mut())
+            .chain([
+                &mut self.king_shelter,
+                &mut self.king_attack,
+                &mut self.doubled_pawn,
+                &mut self.isolated_pawn
+            ])
+            .chain(self.passed_pawn.iter_mut())
+            .collect()
+    }
+
+    // the score of the position from White's point of view
+    pub fn evaluate(&self, position: &Position) -> i32 {
+
+        Square::all()
+            .filter_map(|square| position.board[square].map(|piece| (piece, square)) )
+            .map(|(piece, square)| {
+                let (kind, player) = kind(&piece);
+                let score = self.piece_score(position, &square, kind, &player);
+                match player {
+                    Player::White => score,
+                    Player::Black => -score
+                }
+            })
+            .sum()
+    }
+
+    fn piece_score(
+        &self,
+        position: &Position,
+        square: &Square,
+        kind: usize,
+        player: &Player
+    ) -> i32 {
+
+        let rank = square.rank().index();
+        let index = match player {
+            Player::White => (7 - rank) * 8 + square.file().index(),
+            Player::Black => rank * 8 + square.file().index()
+        };
+
+        let mut score = self.piece_squares[kind][index];
+
+        match kind {
+            PAWN => score += self.material[PAWN] + self.pawn_structure(position, square, player),
+            KING => score += self.king_safety(position, square, player),
+            _ => score += self.material[kind] + self.mobility[kind - 1] * mobility(position, square, kind, player)
+        }
+
+        score
+    }
+
+    fn pawn_structure(
+        &self,
+        position: &Position,
+        square: &Square,
+        player: &Player
+    ) -> i32 {
+
+        let forward = match player {
+            Player::White => 1,
+            Player::Black => -1
+        };
+
+        // pawns of either side on the given file offset, ahead of this pawn
+        let pawns_ahead = |files: i8, owner: Player| (1..8)
+            .filter_map(|ranks| square.offset((ranks * forward, files)) )
+            .any(|s| position.board[s] == Some(Piece::Pawn(owner)) )
+        ;
+        let pawns_on_file = |files: i8| square
+            .file()
+            .offset(files)
+            .is_some_and(|file| Rank::all().any(|rank| position.board[Square::new(file, rank)] == Some(Piece::Pawn(*player)) ))
+        ;
+
+        let mut score = 0;
+
+        if pawns_ahead(0, *player) {
+            score += self.doubled_pawn;
+        }
+        if !pawns_on_file(-1) && !pawns_on_file(1) {
+            score += self.isolated_pawn;
+        }
+        if [-1, 0, 1].iter().all(|files| !pawns_ahead(*files, player.opponent()) ) {
+            let rank = match player {
+                Player::White => square.rank().index(),
+                Player::Black => 7 - square.rank().index()
+            };
+            score += self.passed_pawn[rank];
+        }
+
+        score
+    }
+
+    fn king_safety(
+        &self,
+        position: &Position,
+        square: &Square,
+        player: &Player
+    ) -> i32 {
+
+        let forward = match player {
+            Player::White => 1,
+            Player::Black => -1
+        };
+
+        let shelter = [-1, 0, 1]
+            .iter()
+            .filter_map(|files| square.offset((forward, *files)) )
+            .filter(|s| position.board[*s] == Some(Piece::Pawn(*player)) )
+            .count() as i32
+        ;
+        let attacked = Piece::King(*player).moves()[..8]
+            .iter()
+            .filter_map(|o| square.offset(*o) )
+            .filter(|s| position.is_attacked(s, &player.opponent()) )
+            .count() as i32
+        ;
+
+        self.king_shelter * shelter + self.king_attack * attacked
+    }
+}
+
+// the index of the piece in the parameter lists, and its owner
+fn kind(piece: &Piece) -> (usize, Player) {
+
+    match piece {
+        Piece::Pawn(p) => (0, *p),
+        Piece::Knight(p) => (1, *p),
+        Piece::Bishob(p) => (2, *p),
+        Piece::Rook(p) => (3, *p),
+        Piece::Queen(p) => (4, *p),
+        Piece::King(p) => (5, *p)
+    }
+}
+
+// squares the piece could move to if it didn't have to mind its own king
+fn mobility(
+    position: &Position,
+    square: &Square,
+    kind: usize,
+    player: &Player
+) -> i32 {
+
+    let free = |s: &Square| !position.board[*s].is_some_and(|p| Player::find_player(&p) == *player );
+
+    let rays: Vec<&[(i8, i8); 7]> = match kind {
+        2 => DIAGONALS.iter().collect(),
+        3 => STRAIGHTS.iter().collect(),
+        4 => DIAGONALS.iter().chain(STRAIGHTS.iter()).collect(),
+        _ => {
+            return Piece::Knight(*player)
+                .moves()
+                .iter()
+                .filter_map(|o| square.offset(*o) )
+                .filter(free)
+                .count() as i32
+        }
+    };
+
+    // a ray ends at the first piece, which counts only if it can be captured
+    rays
+        .iter()
+        .map(|ray| {
+            let mut count = 0;
+            for s in ray.iter().map_while(|o| square.offset(*o) ) {
+                if free(&s) {
+                    count += 1;
+                }
+                if position.board[s].is_some() {
+                    break
+                }
+            }
+            count
+        })
+        .sum()
+}
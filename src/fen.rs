@@ -0,0 +1,191 @@
+use crate::{
+    piece::Piece,
+    player::Player,
+    position::{
+        Position,
+        CastlingRights
+    },
+    square::{
+        Square,
+        File,
+        Rank
+    },
+    error::{
+        ChessError,
+        Notation
+    }
+};
+
+impl Position {
+    // the move counters may be left out, as they are in EPD
+    pub fn from_fen(fen: &str) -> Result<Position, ChessError> {
+
+        let err = |offset: usize| ChessError::UnableToParseInput {
+            notation: Notation::Fen,
+            offset,
+            found: fen.get(offset..).unwrap_or_default().split_whitespace().next().unwrap_or_default().to_string()
+        };
+
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        let offset = |field: &str| field.as_ptr() as usize - fen.as_ptr() as usize;
+
+        if fields.len() < 4 {
+            return Err(err(fen.len()))
+        }
+
+        let mut board = [[None; 8]; 8];
+        let ranks: Vec<&str> = fields[0].split('/').collect();
+
+        if ranks.len() != 8 {
+            return Err(err(offset(fields[0])))
+        }
+
+        for (rank, row) in Rank::all().rev().zip(&ranks) {
+
+            let mut file = 0;
+
+            for (i, c) in row.char_indices() {
+                let at = offset(row) + i;
+                match c {
+                    '1'..='8' => file += c as usize - '0' as usize,
+                    _ => {
+                        let player = if c.is_ascii_uppercase() { Player::White } else { Player::Black };
+                        let piece = match c.to_ascii_lowercase() {
+                            'p' => Piece::Pawn(player),
+                            'n' => Piece::Knight(player),
+                            'b' => Piece::Bishob(player),
+                            'r' => Piece::Rook(player),
+                            'q' => Piece::Queen(player),
+                            'k' => Piece::King(player),
+                            _ => return Err(err(at))
+                        };
+                        let square = Square::new(File::new(file).ok_or(err(at))?, rank);
+                        board[square] = Some(piece);
+                        file += 1;
+                    }
+                }
+                if file > 8 {
+                    return Err(err(at))
+                }
+            }
+
+            if file != 8 {
+                return Err(err(offset(row)))
+            }
+        }
+
+        let turn = match fields[1] {
+            "w" => Player::White,
+            "b" => Player::Black,
+            _ => return Err(err(offset(fields[1])))
+        };
+
+        let mut castling = CastlingRights::none();
+
+        if fields[2] != "-" {
+            for (i, c) in fields[2].char_indices() {
+                match c {
+                    'K' => castling.white_king_side = true,
+                    'Q' => castling.white_queen_side = true,
+                    'k' => castling.black_king_side = true,
+                    'q' => castling.black_queen_side = true,
+                    _ => return Err(err(offset(fields[2]) + i))
+                }
+            }
+        }
+
+        let en_passant = match fields[3] {
+            "-" => None,
+            code => Some(code.parse::<Square>().map_err(|_| err(offset(fields[3])) )?)
+        };
+
+        // EPD operations may follow the four fields in place of the counters
+        let counter = |i: usize, default: u32| match fields.get(i).map(|f| f.parse::<u32>() ) {
+            Some(Ok(n)) => n,
+            _ => default
+        };
+
+        Ok(Position {
+            board,
+            turn,
+            castling,
+            en_passant,
+            halfmove_clock: counter(4, 0),
+            fullmove_number: counter(5, 1)
+        })
+    }
+
+    pub fn to_fen(&self) -> String {
+
+        let rows: Vec<String> = Rank::all()
+            .rev()
+            .map(|rank| {
+                let mut row = String::new();
+                let mut empty = 0;
+                for file in File::all() {
+                    match self.board[Square::new(file, rank)] {
+                        Some(piece) => {
+                            if empty > 0 {
+                                row.push_str(&empty.to_string());
+                                empty = 0;
+                            }
+                            row.push(piece.fen_char());
+                        },
+                        None => empty += 1
+                    }
+                }
+                if empty > 0 {
+                    row.push_str(&empty.to_string());
+                }
+                row
+            })
+            .collect()
+        ;
+
+        let turn = match self.turn {
+            Player::White => "w",
+            Player::Black => "b"
+        };
+
+        let castling: String = [
+            (self.castling.white_king_side, 'K'),
+            (self.castling.white_queen_side, 'Q'),
+            (self.castling.black_king_side, 'k'),
+            (self.castling.black_queen_side, 'q')
+        ]
+            .iter()
+            .filter(|(right, _)| *right )
+            .map(|(_, c)| *c )
+            .collect()
+        ;
+
+        format!(
+            "{} {} {} {} {} {}",
+            rows.join("/"),
+            turn,
+            if castling.is_empty() { "-".to_string() } else { castling },
+            self.en_passant.map(|s| s.to_string().to_lowercase() ).unwrap_or("-".to_string()),
+            self.halfmove_clock,
+            self.fullmove_number
+        )
+    }
+}
+
+impl Piece {
+    pub fn fen_char(&self) -> char {
+
+        let (c, player) = match self {
+            Piece::Pawn(p) => ('p', p),
+            Piece::Knight(p) => ('n', p),
+            Piece::Bishob(p) => ('b', p),
+            Piece::Rook(p) => ('r', p),
+            Piece::Queen(p) => ('q', p),
+            Piece::King(p) => ('k', p)
+        };
+
+        match player {
+            Player::White => c.to_ascii_uppercase(),
+            Player::Black => c
+        }
+    }
+}
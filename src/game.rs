@@ -0,0 +1,504 @@
+use std::{
+    io,
+    fs,
+    time::{
+        Duration,
+        Instant
+    }
+};
+#[cfg(feature = "serde")]
+use std::path::Path;
+use crate::{
+    chess::{
+        Chess,
+        ChessState
+    },
+    r#move::{
+        Move,
+        MoveType
+    },
+    player::Player,
+    piece::Piece,
+    square::{
+        Square,
+        File
+    },
+    position::Position,
+    error::{
+        ChessError,
+        Notation
+    },
+    display::DisplayOptions,
+    svg::SvgOptions,
+    book::Book,
+    engine::{
+        Engine,
+        SearchLimits
+    }
+};
+
+// seconds the computer thinks when no time is given
+const ENGINE_MOVE_TIME: u64 = 5;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum GameResult {
+    WhiteWon,
+    BlackWon,
+    Draw,
+    #[default]
+    Unfinished
+}
+
+// the position being played together with how it was reached, the players' clocks and the PGN tags
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Game {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub chess: Chess,
+    // where the moves were played from, the usual setup unless the game began from a FEN
+    #[cfg_attr(feature = "serde", serde(default = "Position::new"))]
+    pub start: Position,
+    pub moves: Vec<Move>,
+    // the moves in SAN, written as they are played
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub san: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub captured: Vec<Piece>,
+    // the tag pairs of a PGN record, e.g. the event and the players' names
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tags: Vec<(String, String)>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub result: GameResult,
+    // the time each player has spent on their moves, White's first
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub clocks: [Duration; 2]
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Game {
+    pub fn new() -> Game {
+
+        Game::from_position(Position::new())
+    }
+
+    pub fn from_position(position: Position) -> Game {
+
+        Game {
+            chess: Chess::from_position(position),
+            start: position,
+            moves: Vec::new(),
+            san: Vec::new(),
+            captured: Vec::new(),
+            tags: Vec::new(),
+            result: GameResult::Unfinished,
+            clocks: [Duration::ZERO; 2]
+        }
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+
+        self.tags
+            .iter()
+            .find(|(n, _)| n == name )
+            .map(|(_, value)| value.as_str() )
+    }
+
+    pub fn make_move(&mut self, m: &Move) -> Result<ChessState, ChessError> {
+
+        let captured = self.chess.captured_piece(m);
+        let san = m.to_san(&self.chess);
+
+        let state = self.chess.make_move(m).map_err(|e| match e {
+            ChessError::CastlingMoveUnavailable {
+                rook,
+                lost_by: None
+            } => ChessError::CastlingMoveUnavailable {
+                rook,
+                lost_by: self.castling_lost_by(&rook)
+            },
+            e => e
+        })?;
+
+        self.moves.push(*m);
+        self.san.push(san);
+        self.captured.extend(captured);
+
+        if state == ChessState::Checkmate {
+            self.result = match self.chess.position.turn {
+                Player::White => GameResult::BlackWon,
+                Player::Black => GameResult::WhiteWon
+            };
+        }
+
+        Ok(state)
+    }
+
+    // the move that took the right to castle with this rook, if the game history has it
+    fn castling_lost_by(&self, rook: &Square) -> Option<Move> {
+
+        let king = Square::new(File::E, rook.rank());
+
+        self.moves
+            .iter()
+            .find(|m| m.from == king || m.from == *rook || m.to == *rook )
+            .copied()
+    }
+
+    pub fn captured_by(
+        &self,
+        player: &Player
+    ) -> Vec<Piece> {
+
+        self.captured
+            .iter()
+            .filter(|p| Player::find_player(p) != *player )
+            .copied()
+            .collect()
+    }
+
+    pub fn play(&mut self) {
+
+        self.play_with(DisplayOptions::default())
+    }
+
+    pub fn play_with(&mut self, options: DisplayOptions) {
+
+        self.play_with_engine(options, &Engine::new())
+    }
+
+    // the engine answers the 'go' command
+    pub fn play_with_engine(
+        &mut self,
+        options: DisplayOptions,
+        engine: &Engine
+    ) {
+
+        println!("To move a piece, type it's current and new spot (e.g 'A1 A2')");
+        #[cfg(feature = "serde")]
+        println!("To save or resume a game, type 'save <file>' or 'load <file>'");
+        println!("To draw the board as an SVG image, type 'svg <file>'");
+        println!("To write the game as PGN, type 'pgn <file>'");
+        println!("To go over the moves played so far, type 'replay', or 'replay <file>' for a PGN game");
+        println!("To see the opening book moves, type 'book <file>', or 'book <file> play' to play one");
+        println!("To see where a piece can move, type 'moves <spot>', or 'hint' for all your moves");
+        println!("To let the computer play the move, type 'go', or 'go <seconds>' to give it more time");
+
+        let mut played = self.moves.len();
+        let mut turn_started = Instant::now();
+
+        loop {
+
+            // the time since the last move goes to the player who made it
+            if self.moves.len() != played {
+                if self.moves.len() == played + 1 {
+                    self.clocks[self.chess.position.turn.opponent() as usize] += turn_started.elapsed();
+                }
+                played = self.moves.len();
+                turn_started = Instant::now();
+            }
+
+            println!("{}", self.display(options));
+            if !self.moves.is_empty() {
+                println!("Moves: {}", self.numbered_moves().join(" "));
+            }
+            if let Some(opening) = self.opening() {
+                println!("Opening: {}", opening);
+            }
+            if !self.captured.is_empty() {
+                println!("Captured by White: {}", self.display(options).captured(&Player::White));
+                println!("Captured by Black: {}", self.display(options).captured(&Player::Black));
+            }
+            println!("{}'s turn.", self.chess.position.turn);
+
+            let mut input = String::new();
+            if io::stdin().read_line(&mut input).expect("Failed to read line") == 0 {
+                break
+            }
+
+            if self.run_command(&input, &options, engine) {
+                continue
+            }
+
+            let mut m = match Move::from_input(&self.chess, input) {
+                Ok(m) => m,
+                Err(e) => {
+                    println!("{}", e);
+                    continue
+                }
+            };
+
+            if MoveType::determine_type(&self.chess, &m) == MoveType::Promotion
+                && m.process_move(&mut self.chess.clone(), Some(&self.chess.position.turn)).is_ok() {
+                m.promotion = Some(self.ask_promotion());
+            }
+
+            match self.make_move(&m) {
+                Ok(ChessState::Checkmate) => {
+                    println!("{}", self.display(options));
+                    println!("Checkmate! {} won the game.", self.chess.position.turn.opponent());
+                    println!("Type 'replay' to go over the game or press Enter to quit.");
+
+                    let mut input = String::new();
+                    io::stdin().read_line(&mut input).expect("Failed to read line");
+
+                    if input.trim() == "replay" {
+                        self.replay(options);
+                    }
+                    break
+                },
+                Ok(ChessState::Check {
+                    checked_player,
+                    moves_left: _
+                }) => println!("{} is in a check.", checked_player),
+                Ok(ChessState::Normal) => (),
+                Err(e) => println!("{}", e)
+            }
+        }
+    }
+
+    fn ask_promotion(&self) -> Piece {
+
+        println!("Your pawn reached the last row.");
+
+        let player = self.chess.position.turn;
+
+        loop {
+            println!("Choose the piece you want to change it to - 'QUEEN', 'ROOK', 'BISHOB' or 'KNIGHT'.");
+
+            let mut s = String::new();
+            io::stdin().read_line(&mut s).expect("Failed to read line");
+
+            match s.trim() {
+                "QUEEN" => return Piece::Queen(player),
+                "ROOK" => return Piece::Rook(player),
+                "BISHOB" => return Piece::Bishob(player),
+                "KNIGHT" => return Piece::Knight(player),
+                _ => println!("Invalid input, try again.")
+            }
+        }
+    }
+
+    fn run_command(
+        &mut self,
+        input: &str,
+        options: &DisplayOptions,
+        engine: &Engine
+    ) -> bool {
+
+        match input.split_whitespace().collect::<Vec<&str>>().as_slice() {
+            ["moves", code] => {
+                match code.parse::<Square>() {
+                    Ok(square) => self.show_moves_from(&square, options),
+                    Err(_) => println!("{}", ChessError::UnableToParseInput {
+                        notation: Notation::Move,
+                        offset: input.rfind(code).unwrap_or(0),
+                        found: code.to_string()
+                    })
+                }
+                true
+            },
+            ["hint"] => {
+                let moves: Vec<String> = self.chess
+                    .legal_moves()
+                    .iter()
+                    .map(|m| m.to_san(&self.chess) )
+                    .collect()
+                ;
+                println!("Legal moves: {}", moves.join(" "));
+                true
+            },
+            ["book", path] => {
+                match Book::open(path) {
+                    Ok(book) => {
+                        let moves = book.moves(&self.chess);
+                        let total: u32 = moves.iter().map(|(_, w)| *w as u32 ).sum::<u32>().max(1);
+                        let moves: Vec<String> = moves
+                            .iter()
+                            .map(|(m, w)| format!("{} ({}%)", m.to_san(&self.chess), *w as u32 * 100 / total) )
+                            .collect()
+                        ;
+                        if moves.is_empty() {
+                            println!("The position is not in the book.");
+                        } else {
+                            println!("Book moves: {}", moves.join(" "));
+                        }
+                    },
+                    Err(e) => println!("Couldn't open the book: {}", e)
+                }
+                true
+            },
+            ["book", path, "play"] => {
+                match Book::open(path) {
+                    Ok(book) => match book.pick(&self.chess) {
+                        Some(m) => {
+                            let san = m.to_san(&self.chess);
+                            match self.make_move(&m) {
+                                Ok(_) => println!("Played {} from the book.", san),
+                                Err(e) => println!("{}", e)
+                            }
+                        },
+                        None => println!("The position is not in the book.")
+                    },
+                    Err(e) => println!("Couldn't open the book: {}", e)
+                }
+                true
+            },
+            ["go"] => {
+                self.play_engine_move(engine, Duration::from_secs(ENGINE_MOVE_TIME));
+                true
+            },
+            ["go", seconds] => {
+                match seconds.parse::<f64>().ok().and_then(|s| Duration::try_from_secs_f64(s).ok() ) {
+                    Some(move_time) => self.play_engine_move(engine, move_time),
+                    None => println!("Invalid input, try again.")
+                }
+                true
+            },
+            ["replay", path] => {
+                match Game::load_pgn(path) {
+                    Ok(game) => game.replay(*options),
+                    Err(e) => println!("Couldn't read the game: {}", e)
+                }
+                true
+            },
+            ["replay"] => {
+                self.replay(*options);
+                true
+            },
+            ["svg", path] => {
+                let svg_options = SvgOptions {
+                    orientation: options.orientation,
+                    highlights: self.moves.last().map(|m| vec![m.from, m.to]).unwrap_or_default(),
+                    ..SvgOptions::default()
+                };
+                match fs::write(path, self.chess.to_svg(&svg_options)) {
+                    Ok(()) => println!("Board diagram written to '{}'.", path),
+                    Err(e) => println!("Couldn't write the diagram: {}", e)
+                }
+                true
+            },
+            ["pgn", path] => {
+                match fs::write(path, self.to_pgn()) {
+                    Ok(()) => println!("Game written to '{}'.", path),
+                    Err(e) => println!("Couldn't write the game: {}", e)
+                }
+                true
+            },
+            #[cfg(feature = "serde")]
+            ["save", path] => {
+                match self.save(path) {
+                    Ok(()) => println!("Game saved to '{}'.", path),
+                    Err(e) => println!("Couldn't save the game: {}", e)
+                }
+                true
+            },
+            #[cfg(feature = "serde")]
+            ["load", path] => {
+                match Game::load(path) {
+                    Ok(game) => {
+                        *self = game;
+                        println!("Game loaded from '{}'.", path);
+                    },
+                    Err(e) => println!("Couldn't load the game: {}", e)
+                }
+                true
+            },
+            _ => false
+        }
+    }
+
+    fn show_moves_from(
+        &self,
+        tile: &Square,
+        options: &DisplayOptions
+    ) {
+
+        let piece = match self.chess.position.find_piece(tile) {
+            Some(piece) if Player::find_player(&piece) == self.chess.position.turn => piece,
+            Some(_) => {
+                println!("{}", ChessError::PieceBelongsToOpponent {
+                    tile: *tile
+                });
+                return
+            },
+            None => {
+                println!("{}", ChessError::EmptyTile {
+                    tile: *tile
+                });
+                return
+            }
+        };
+
+        let moves = self.chess.legal_moves_from(tile);
+
+        if options.colored {
+            println!("{}", self.display(DisplayOptions {
+                selected: Some(*tile),
+                ..*options
+            }));
+        }
+
+        if moves.is_empty() {
+            println!("{} can't move anywhere.", piece);
+        } else {
+            let destinations: Vec<String> = moves
+                .iter()
+                .map(|m| m.to.to_string() )
+                .collect()
+            ;
+            println!("{} can move to: {}", piece, destinations.join(" "));
+        }
+    }
+
+    fn play_engine_move(
+        &mut self,
+        engine: &Engine,
+        move_time: Duration
+    ) {
+
+        let result = engine.search(&self.chess, &SearchLimits {
+            move_time: Some(move_time),
+            ..SearchLimits::default()
+        });
+
+        match result.best_move {
+            Some(m) => {
+                let san = m.to_san(&self.chess);
+                match self.make_move(&m) {
+                    Ok(_) => println!("Computer played {} (depth {}, score {:+.2}).", san, result.depth, result.score as f64 / 100.0),
+                    Err(e) => println!("{}", e)
+                }
+            },
+            None => println!("There are no moves to play.")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+
+        let json = serde_json::to_string_pretty(self)?;
+
+        fs::write(path, json)
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Game> {
+
+        let json = fs::read_to_string(path)?;
+        let mut game: Game = serde_json::from_str(&json)?;
+
+        // games saved before the SAN was kept have it written again
+        if game.san.len() != game.moves.len() {
+            game.san = game.history().into_iter().map(|(san, _)| san ).collect();
+        }
+
+        Ok(game)
+    }
+}
@@ -1,10 +1,28 @@
 pub mod chess;
+pub mod game;
 pub mod r#move;
 pub mod piece;
 pub mod player;
+pub mod square;
+pub mod position;
 pub mod constant;
 pub mod display;
 pub mod error;
+pub mod svg;
+pub mod book;
+pub mod eco;
+pub mod pgn;
+pub mod engine;
+pub mod tt;
+pub mod see;
+pub mod uci;
+pub mod fen;
+pub mod eval;
+pub mod tune;
+pub mod difficulty;
+pub mod replay;
+#[cfg(feature = "tui")]
+pub mod tui;
 
 /*
 
@@ -20,17 +38,100 @@ All moves allowed, incl. promotion, en passant and castling.
 #[cfg(test)]
 mod tests {
 
+    use std::{
+        collections::HashSet,
+        sync::atomic::Ordering,
+        thread,
+        time::Duration
+    };
     use crate::{
         chess::{
             Chess,
             ChessState
         },
+        game::{
+            Game,
+            GameResult
+        },
         piece::Piece,
         player::Player,
         r#move::Move,
-        error::ChessError
+        position::{
+            Position,
+            CastlingRights
+        },
+        square::{
+            Square,
+            File,
+            Rank
+        },
+        error::{
+            ChessError,
+            Notation
+        },
+        display::{
+            DisplayOptions,
+            Orientation
+        },
+        svg::SvgOptions,
+        constant::{
+            RESET,
+            LIGHT_TILE,
+            DARK_TILE,
+            LAST_MOVE_TILE,
+            CHECK_TILE,
+            DESTINATION_TILE,
+            CURSOR_TILE,
+            WHITE_PIECE,
+            BLACK_PIECE
+        },
+        book::Book,
+        engine::{
+            Engine,
+            SearchLimits,
+            TimeBudget,
+            TimeControl,
+            MATE
+        },
+        difficulty::{
+            Difficulty,
+            Skill
+        },
+        uci::Uci,
+        pgn::parse_pgn,
+        eval::EvalParams,
+        tune::{
+            Tuner,
+            read_epd,
+            read_pgn
+        },
+        tt::{
+            Bound,
+            TranspositionTable,
+            TtEntry
+        }
     };
 
+    fn sq(code: &str) -> Square {
+        code.parse().unwrap()
+    }
+
+    // the moves of the Scholar's mate before White's Qxf7#
+    const SCHOLARS_MATE: [&str; 6] = ["E2 E4", "E7 E5", "F1 C4", "B8 C6", "D1 H5", "G8 F6"];
+
+    // a game from the start position with moves entered as in play
+    fn play(inputs: &[&str]) -> Game {
+
+        let mut test_game = Game::new();
+
+        for input in inputs {
+            let m = Move::from_input(&test_game.chess, input.to_string()).unwrap();
+            assert!(test_game.make_move(&m).is_ok());
+        }
+
+        test_game
+    }
+
     #[test]
     fn it_works() {
         
@@ -39,86 +140,22 @@ mod tests {
         let mut player_turn = [Player::Black, Player::White].repeat(15).into_iter();
 
         let moves = [
-            Move {
-                piece: Piece::Pawn(Player::White),
-                from: (1, 3),
-                to: (3, 3)
-            },
-            Move {
-                piece: Piece::Pawn(Player::Black),
-                from: (6, 4),
-                to: (4, 4)
-            },
-            Move {
-                piece: Piece::Pawn(Player::White),
-                from: (3, 3),
-                to: (4, 4)
-            },
-            Move {
-                piece: Piece::Pawn(Player::Black),
-                from: (6, 3),
-                to: (4, 3)
-            },
-            Move { // en passant
-                piece: Piece::Pawn(Player::White),
-                from: (4, 4),
-                to: (5, 3)
-            },
-            Move {
-                piece: Piece::Queen(Player::Black),
-                from: (7, 3),
-                to: (5, 3)
-            },
-            Move {
-                piece: Piece::Queen(Player::White),
-                from: (0, 3),
-                to: (5, 3)
-            },
-            Move {
-                piece: Piece::Bishob(Player::Black),
-                from: (7, 5),
-                to: (5, 3)
-            },
-            Move {
-                piece: Piece::Pawn(Player::White),
-                from: (1, 4),
-                to: (2, 4)
-            },
-            Move {
-                piece: Piece::Knight(Player::Black),
-                from: (7, 6),
-                to: (5, 5)
-            },
-            Move {
-                piece: Piece::Pawn(Player::White),
-                from: (1, 5),
-                to: (3, 5)
-            },
-            Move { // castling
-                piece: Piece::King(Player::Black),
-                from: (7, 4),
-                to: (7, 6)
-            },
-            Move {
-                piece: Piece::Knight(Player::White),
-                from: (0, 1),
-                to: (2, 2)
-            },
-            Move {
-                piece: Piece::Rook(Player::Black),
-                from: (7, 5),
-                to: (7, 4)
-            },
-            Move {
-                piece: Piece::Knight(Player::White),
-                from: (0, 6),
-                to: (2, 5)
-            },
-            Move { // rook is pinning pawn
-                piece: Piece::Bishob(Player::Black),
-                from: (5, 3),
-                to: (3, 5)
-            },
+            Move::new(Piece::Pawn(Player::White), sq("D2"), sq("D4")),
+            Move::new(Piece::Pawn(Player::Black), sq("E7"), sq("E5")),
+            Move::new(Piece::Pawn(Player::White), sq("D4"), sq("E5")),
+            Move::new(Piece::Pawn(Player::Black), sq("D7"), sq("D5")),
+            Move::new(Piece::Pawn(Player::White), sq("E5"), sq("D6")), // en passant
+            Move::new(Piece::Queen(Player::Black), sq("D8"), sq("D6")),
+            Move::new(Piece::Queen(Player::White), sq("D1"), sq("D6")),
+            Move::new(Piece::Bishob(Player::Black), sq("F8"), sq("D6")),
+            Move::new(Piece::Pawn(Player::White), sq("E2"), sq("E3")),
+            Move::new(Piece::Knight(Player::Black), sq("G8"), sq("F6")),
+            Move::new(Piece::Pawn(Player::White), sq("F2"), sq("F4")),
+            Move::new(Piece::King(Player::Black), sq("E8"), sq("G8")), // castling
+            Move::new(Piece::Knight(Player::White), sq("B1"), sq("C3")),
+            Move::new(Piece::Rook(Player::Black), sq("F8"), sq("E8")),
+            Move::new(Piece::Knight(Player::White), sq("G1"), sq("F3")),
+            Move::new(Piece::Bishob(Player::Black), sq("D6"), sq("F4")), // rook is pinning pawn
         ];
 
         /*
@@ -139,139 +176,70 @@ mod tests {
             .iter()
             .for_each(|m| {
                 assert!(m.process_move(&mut test_chess, None).is_ok());
-                test_chess.turn = player_turn.next().unwrap();
+                test_chess.position.turn = player_turn.next().unwrap();
             })
         ;
 
-        let move_pinned_piece = Move {
-            piece: Piece::Pawn(Player::White),
-            from: (2, 4),
-            to: (3, 5)
-        };
+        let move_pinned_piece = Move::new(Piece::Pawn(Player::White), sq("E3"), sq("F4"));
 
         assert_eq!(
             move_pinned_piece.process_move(&mut test_chess, None),
-            Err(ChessError::KingCompromised)
+            Err(ChessError::KingCompromised {
+                attacker: Piece::Rook(Player::Black),
+                tile: sq("E8")
+            })
         );
 
         let moves = [
-            Move {
-                piece: Piece::Pawn(Player::White),
-                from: (1, 1),
-                to: (2, 1)
-            },
-            Move {
-                piece: Piece::Knight(Player::Black),
-                from: (5, 5),
-                to: (3, 6)
-            },
-            Move {
-                piece: Piece::Knight(Player::White),
-                from: (2, 5),
-                to: (4, 6)
-            },
-            Move {
-                piece: Piece::Knight(Player::Black),
-                from: (3, 6),
-                to: (1, 5)
-            },
-            Move {
-                piece: Piece::Bishob(Player::White),
-                from: (0, 2),
-                to: (1, 1)
-            },
-            Move {
-                piece: Piece::Bishob(Player::Black),
-                from: (7, 2),
-                to: (3, 6)
-            },
-            Move {
-                piece: Piece::Knight(Player::White),
-                from: (2, 2),
-                to: (4, 1)
-            }
+            Move::new(Piece::Pawn(Player::White), sq("B2"), sq("B3")),
+            Move::new(Piece::Knight(Player::Black), sq("F6"), sq("G4")),
+            Move::new(Piece::Knight(Player::White), sq("F3"), sq("G5")),
+            Move::new(Piece::Knight(Player::Black), sq("G4"), sq("F2")),
+            Move::new(Piece::Bishob(Player::White), sq("C1"), sq("B2")),
+            Move::new(Piece::Bishob(Player::Black), sq("C8"), sq("G4")),
+            Move::new(Piece::Knight(Player::White), sq("C3"), sq("B5"))
         ];
 
         moves
             .iter()
             .for_each(|m| {
                 assert!(m.process_move(&mut test_chess, None).is_ok());
-                test_chess.turn = player_turn.next().unwrap();
+                test_chess.position.turn = player_turn.next().unwrap();
             })
         ;
 
-        let check = Move {
-            piece: Piece::Rook(Player::Black),
-            from: (7, 4),
-            to: (2, 4)
-        };
+        let check = Move::new(Piece::Rook(Player::Black), sq("E8"), sq("E3"));
 
         assert_eq!(
             check.process_move(&mut test_chess, None),
             Ok(ChessState::Check {
                 checked_player: Player::White,
                 moves_left: vec![
-                    Move {
-                        piece: Piece::King(Player::White),
-                        from: (0, 4),
-                        to: (1, 5)
-                    },
-                    Move {
-                        piece: Piece::King(Player::White),
-                        from: (0, 4),
-                        to: (1, 3)
-                    },
-                    Move {
-                        piece: Piece::Bishob(Player::White),
-                        from: (0, 5),
-                        to: (1, 4)
-                    },
+                    Move::new(Piece::King(Player::White), sq("E1"), sq("F2")),
+                    Move::new(Piece::King(Player::White), sq("E1"), sq("D2")),
+                    Move::new(Piece::Bishob(Player::White), sq("F1"), sq("E2")),
                 ]
             })
         );
-        test_chess.turn = player_turn.next().unwrap();
+        test_chess.position.turn = player_turn.next().unwrap();
 
         let moves = [
-                Move {
-                piece: Piece::Bishob(Player::White),
-                from: (0, 5),
-                to: (1, 4)
-            },
-            Move {
-                piece: Piece::Bishob(Player::Black),
-                from: (3, 6),
-                to: (1, 4)
-            },
-            Move {
-                piece: Piece::Pawn(Player::White),
-                from: (1, 2),
-                to: (2, 2)
-            },
-            Move { // check
-                piece: Piece::Bishob(Player::Black),
-                from: (1, 4),
-                to: (3, 2)
-            },
-            Move {
-                piece: Piece::King(Player::White),
-                from: (0, 4),
-                to: (1, 3)
-            }
+                Move::new(Piece::Bishob(Player::White), sq("F1"), sq("E2")),
+            Move::new(Piece::Bishob(Player::Black), sq("G4"), sq("E2")),
+            Move::new(Piece::Pawn(Player::White), sq("C2"), sq("C3")),
+            Move::new(Piece::Bishob(Player::Black), sq("E2"), sq("C4")), // check
+            Move::new(Piece::King(Player::White), sq("E1"), sq("D2"))
         ];
 
         moves
             .iter()
             .for_each(|m| {
                 assert!(m.process_move(&mut test_chess, None).is_ok());
-                test_chess.turn = player_turn.next().unwrap();
+                test_chess.position.turn = player_turn.next().unwrap();
             })
         ;
 
-        let checkmate = Move {
-            piece: Piece::Rook(Player::Black),
-            from: (2, 4),
-            to: (1, 4)
-        };
+        let checkmate = Move::new(Piece::Rook(Player::Black), sq("E3"), sq("E2"));
 
         assert_eq!(
             checkmate.process_move(&mut test_chess, None),
@@ -292,4 +260,963 @@ mod tests {
 
         */
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+
+        let mut test_game = Game::new();
+
+        let m = Move::new(Piece::Pawn(Player::White), sq("E2"), sq("E4"));
+
+        assert!(test_game.make_move(&m).is_ok());
+
+        assert_eq!(
+            serde_json::to_string(&m).unwrap(),
+            r#"{"piece":{"kind":"pawn","player":"white"},"from":[1,4],"to":[3,4]}"#
+        );
+
+        let state = ChessState::Check {
+            checked_player: Player::Black,
+            moves_left: Vec::new()
+        };
+
+        assert_eq!(
+            serde_json::to_string(&state).unwrap(),
+            r#"{"state":"check","checked_player":"black","moves_left":[]}"#
+        );
+
+        test_game.clocks = [Duration::from_secs(75), Duration::from_millis(1500)];
+        test_game.tags.push((String::from("White"), String::from("Anderssen")));
+
+        let json = serde_json::to_string(&test_game).unwrap();
+        let loaded: Game = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(loaded.chess.position, test_game.chess.position);
+        assert_eq!(loaded.chess.position.turn, Player::Black);
+        assert_eq!(loaded.chess.state, ChessState::Normal);
+        assert_eq!(loaded.moves, vec![m]);
+        assert_eq!(loaded.san, vec![String::from("e4")]);
+        assert_eq!(loaded.clocks, test_game.clocks);
+        assert_eq!(loaded.tag("White"), Some("Anderssen"));
+    }
+
+    #[test]
+    fn display_orientation() {
+
+        let test_chess = Chess::new();
+
+        let white = test_chess.to_string();
+        let black = test_chess
+            .display(DisplayOptions {
+                orientation: Orientation::Black,
+                ..DisplayOptions::default()
+            })
+            .to_string()
+        ;
+
+        assert_eq!(
+            white.lines().take(2).collect::<Vec<&str>>(),
+            vec!["   A  B  C  D  E  F  G  H", "8 |Rb|Kb|Bb|Qb|*b|Bb|Kb|Rb|"]
+        );
+        assert_eq!(
+            black.lines().take(2).collect::<Vec<&str>>(),
+            vec!["   H  G  F  E  D  C  B  A", "1 |Rw|Kw|Bw|*w|Qw|Bw|Kw|Rw|"]
+        );
+    }
+
+    #[test]
+    fn board_coordinates() {
+
+        let white = Chess::new();
+        let mut black = Chess::new();
+        black.position.turn = Player::Black;
+
+        let options = DisplayOptions {
+            orientation: Orientation::Turn,
+            ..DisplayOptions::default()
+        };
+        let (white, black) = (white.display(options), black.display(options));
+
+        // a click anywhere on a tile picks it, the rank labels and the borders pick nothing
+        assert_eq!(white.tile_at(1, 2), Some(sq("A8")));
+        assert_eq!(white.tile_at(8, 25), Some(sq("H1")));
+        assert_eq!(white.tile_at(8, 14), Some(sq("E1")));
+        assert_eq!(&white.to_string().lines().nth(8).unwrap()[14..17], "|*w");
+        assert_eq!(black.tile_at(1, 2), Some(sq("H1")));
+        assert_eq!(black.tile_at(8, 25), Some(sq("A8")));
+        assert_eq!(black.tile_at(8, 14), Some(sq("D8")));
+        assert_eq!(&black.to_string().lines().nth(8).unwrap()[14..17], "|Qb");
+        assert_eq!(white.tile_at(0, 5), None);
+        assert_eq!(white.tile_at(9, 5), None);
+        assert_eq!(white.tile_at(3, 1), None);
+        assert_eq!(white.tile_at(3, 26), None);
+
+        // up and right are as seen on the screen
+        assert_eq!(white.step_cursor(sq("E2"), (1, 1)), sq("F3"));
+        assert_eq!(black.step_cursor(sq("E2"), (1, 1)), sq("D1"));
+        assert_eq!(white.step_cursor(sq("H8"), (1, 1)), sq("H8"));
+        assert_eq!(black.step_cursor(sq("A1"), (1, -1)), sq("B1"));
+    }
+
+    #[test]
+    fn unicode_display() {
+
+        let test_game = play(&["E2 E4", "D7 D5", "E4 D5"]);
+
+        let options = DisplayOptions {
+            unicode: true,
+            ..DisplayOptions::default()
+        };
+        let board = test_game.display(options).to_string();
+
+        assert_eq!(
+            board.lines().skip(1).step_by(3).collect::<Vec<&str>>(),
+            vec!["8 |♜ |♞ |♝ |♛ |♚ |♝ |♞ |♜ |", "5 |  |  |  |♙ |  |  |  |  |", "2 |♙ |♙ |♙ |♙ |  |♙ |♙ |♙ |"]
+        );
+        assert_eq!(test_game.display(options).captured(&Player::White), "♟ +1");
+        assert_eq!(test_game.display(DisplayOptions::default()).captured(&Player::White), "Pb +1");
+    }
+
+    #[test]
+    fn colored_display() {
+
+        let test_game = play(&["E2 E4", "F7 F6", "D1 H5"]);
+
+        let board = test_game
+            .display(DisplayOptions {
+                colored: true,
+                selected: Some(sq("G7")),
+                ..DisplayOptions::default()
+            })
+            .to_string()
+        ;
+        let rows: Vec<&str> = board.lines().skip(1).collect();
+
+        assert!(rows.iter().all(|row| row.ends_with(RESET) ));
+        assert!(rows[7].starts_with(&format!("1 {}{} Rw", DARK_TILE, WHITE_PIECE)));
+        assert!(rows[7].contains(&format!("{}{} Bw", LIGHT_TILE, WHITE_PIECE)));
+
+        // the king in check, the last move and where the selected pawn may go
+        assert!(rows[0].contains(&format!("{}{} *b", CHECK_TILE, BLACK_PIECE)));
+        assert!(rows[3].contains(&format!("{}{} Qw", LAST_MOVE_TILE, WHITE_PIECE)));
+        assert!(rows[7].contains(&format!("{}   ", LAST_MOVE_TILE)));
+        assert!(rows[1].contains(&format!("{}{} Pb", DESTINATION_TILE, BLACK_PIECE)));
+        assert!(rows[2].contains(&format!("{}   ", DESTINATION_TILE)));
+        assert_eq!(board.matches(DESTINATION_TILE).count(), 2);
+
+        let cursor = test_game
+            .display(DisplayOptions {
+                colored: true,
+                cursor: Some(sq("E8")),
+                ..DisplayOptions::default()
+            })
+            .to_string()
+        ;
+        assert!(cursor.contains(&format!("{}{} *b", CURSOR_TILE, BLACK_PIECE)));
+        assert!(!cursor.contains(CHECK_TILE));
+    }
+
+    #[test]
+    fn svg_diagram() {
+
+        let test_chess = Chess::new();
+
+        let svg = test_chess.to_svg(&SvgOptions {
+            arrows: vec![(sq("E2"), sq("E4"))],
+            highlights: vec![sq("E2")],
+            ..SvgOptions::default()
+        });
+
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("font-size=\"38\"").count(), 32);
+        assert_eq!(svg.matches("<line").count(), 1);
+        assert_eq!(svg.matches("fill-opacity").count(), 1);
+    }
+
+    #[test]
+    fn san_history() {
+
+        let castling = play(&["E2 E4", "E7 E5", "G1 F3", "B8 C6", "F1 C4", "G8 F6", "E1 G1", "F6 E4"]);
+
+        assert_eq!(
+            castling.san,
+            vec!["e4", "e5", "Nf3", "Nc6", "Bc4", "Nf6", "O-O", "Nxe4"]
+        );
+
+        let mate = play(&[&SCHOLARS_MATE[..], &["H5 F7"]].concat());
+
+        assert_eq!(mate.chess.state, ChessState::Checkmate);
+        assert_eq!(mate.result, GameResult::WhiteWon);
+        assert_eq!(mate.san.last().unwrap(), "Qxf7#");
+        assert_eq!(mate.history().len(), 7);
+
+        // the knight that could block the rook is pinned by the bishop
+        let mut pinned = Game::from_position(Position::from_fen("7k/7p/5n1P/4B3/8/8/8/K2R4 w - - 0 1").unwrap());
+
+        assert_eq!(pinned.make_move(&Move::new(Piece::Rook(Player::White), sq("D1"), sq("D8"))), Ok(ChessState::Checkmate));
+        assert_eq!(pinned.san, vec!["Rd8#"]);
+        assert_eq!(pinned.result, GameResult::WhiteWon);
+
+        // a check may be answered with a check of its own
+        let mut cross_check = Game::from_position(Position::from_fen("r6k/8/8/Q7/8/8/8/4K3 b - - 0 1").unwrap());
+
+        assert!(cross_check.make_move(&Move::new(Piece::Rook(Player::Black), sq("A8"), sq("E8"))).is_ok());
+        assert!(matches!(
+            cross_check.make_move(&Move::new(Piece::Queen(Player::White), sq("A5"), sq("E5"))),
+            Ok(ChessState::Check { checked_player: Player::Black, .. })
+        ));
+        assert_eq!(cross_check.san, vec!["Re8+", "Qe5+"]);
+
+        // a game set up from a FEN is numbered and replayed from there
+        let fen = "4k3/8/8/8/8/8/4P3/4K3 b - - 0 12";
+        let games = parse_pgn(&format!("[White \"Morphy\"]\n[FEN \"{}\"]\n\n12... Kd7 13. e4 Kc6 1/2-1/2", fen)).unwrap();
+        let endgame = Game::from_pgn(&games[0]).unwrap();
+
+        assert_eq!(endgame.numbered_moves(), vec!["12... Kd7", "13. e4 Kc6"]);
+        assert_eq!(endgame.history()[0].1.position.board[sq("D7")], Some(Piece::King(Player::Black)));
+        assert_eq!(endgame.opening(), None);
+        assert!(endgame.to_pgn().contains(&format!("[FEN \"{}\"]", fen)));
+
+        // the tags and the result of the record stay with the game
+        assert_eq!(endgame.result, GameResult::Draw);
+        assert!(endgame.to_pgn().contains("[White \"Morphy\"]\n"));
+        assert!(endgame.to_pgn().ends_with("13. e4 Kc6 1/2-1/2\n"));
+    }
+
+    #[test]
+    fn captured_pieces() {
+
+        let test_game = play(&["E2 E4", "D7 D5", "E4 D5", "E7 E5", "D5 E6", "D8 D2", "B1 D2"]);
+
+        assert_eq!(
+            test_game.captured_by(&Player::White),
+            vec![Piece::Pawn(Player::Black), Piece::Pawn(Player::Black), Piece::Queen(Player::Black)]
+        );
+        assert_eq!(test_game.captured_by(&Player::Black), vec![Piece::Pawn(Player::White)]);
+        assert_eq!(test_game.chess.position.material_balance(), 10);
+        assert_eq!(test_game.display(DisplayOptions::default()).captured(&Player::White), "Pb Pb Qb +10");
+    }
+
+    #[test]
+    fn legal_moves() {
+
+        let mut test_chess = Chess::new();
+
+        assert_eq!(test_chess.legal_moves().len(), 20);
+        assert_eq!(
+            test_chess
+                .legal_moves_from(&sq("G1"))
+                .iter()
+                .map(|m| m.to )
+                .collect::<Vec<Square>>(),
+            vec![sq("H3"), sq("F3")]
+        );
+
+        let m = Move::from_input(&test_chess, String::from("E2 E4")).unwrap();
+        assert!(test_chess.make_move(&m).is_ok());
+
+        assert_eq!(test_chess.legal_moves().len(), 20);
+        assert!(test_chess.legal_moves_from(&sq("D2")).is_empty());
+    }
+
+    #[test]
+    fn move_generation() {
+
+        // the number of move sequences of a given length, a well-known count for each position
+        fn perft(chess: &Chess, depth: u32) -> u64 {
+
+            if depth == 0 {
+                return 1
+            }
+
+            chess
+                .legal_moves()
+                .iter()
+                .map(|m| {
+                    let mut child = chess.clone();
+                    assert!(child.make_move(m).is_ok(), "{} in {}", m, chess.position.to_fen());
+                    perft(&child, depth - 1)
+                })
+                .sum()
+        }
+
+        let count = |fen: &str, depth: u32| perft(&Chess::from_position(Position::from_fen(fen).unwrap()), depth);
+
+        assert_eq!(perft(&Chess::new(), 3), 8902);
+        assert_eq!(count("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", 2), 2039);
+        assert_eq!(count("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 4), 43238);
+        assert_eq!(count("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", 3), 9467);
+    }
+
+    #[test]
+    fn illegal_move_diagnostics() {
+
+        let mut test_game = Game::new();
+
+        let m = Move::from_input(&test_game.chess, String::from("A1 A3")).unwrap();
+        let e = test_game.make_move(&m).unwrap_err();
+        assert_eq!(e, ChessError::PathIsBlocked {
+            blocker: Piece::Pawn(Player::White),
+            tile: sq("A2")
+        });
+        assert_eq!(e.to_string(), "Can't move there; path is blocked by Pw on A2.");
+
+        for input in ["G1 F3", "G8 F6", "E2 E4", "E7 E5", "F1 E2", "F8 E7", "E1 F1", "E8 F8", "F1 E1", "F8 E8"] {
+            let m = Move::from_input(&test_game.chess, String::from(input)).unwrap();
+            assert!(test_game.make_move(&m).is_ok());
+        }
+
+        let m = Move::from_input(&test_game.chess, String::from("E1 G1")).unwrap();
+        assert_eq!(
+            test_game.make_move(&m),
+            Err(ChessError::CastlingMoveUnavailable {
+                rook: sq("H1"),
+                lost_by: Some(Move::new(Piece::King(Player::White), sq("E1"), sq("F1")))
+            })
+        );
+
+        // only a knight, bishop, rook or queen of the pawn's own color may be chosen
+        let test_chess = Chess::from_position(Position::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap());
+
+        for piece in [Piece::King(Player::White), Piece::Pawn(Player::White), Piece::Queen(Player::Black)] {
+            let m = Move {
+                promotion: Some(piece),
+                ..Move::new(Piece::Pawn(Player::White), sq("B7"), sq("B8"))
+            };
+            assert_eq!(test_chess.clone().make_move(&m), Err(ChessError::InvalidPromotion {
+                piece
+            }));
+        }
+
+        let mut promoted = test_chess.clone();
+        let m = Move {
+            promotion: Some(Piece::Knight(Player::White)),
+            ..Move::new(Piece::Pawn(Player::White), sq("B7"), sq("B8"))
+        };
+        assert!(promoted.make_move(&m).is_ok());
+        assert_eq!(promoted.position.board[sq("B8")], Some(Piece::Knight(Player::White)));
+
+        // a pawn on the last rank but one checks the king it attacks, and no king may step beside it
+        let pawn_check = Chess::from_position(Position::from_fen("4k3/3P4/8/8/8/8/8/4K3 b - - 0 1").unwrap());
+
+        assert_eq!(pawn_check.is_check(&Player::White, None), Some(vec![Move::new(Piece::Pawn(Player::White), sq("D7"), sq("E8"))]));
+
+        let mut pawn_on_seventh = Chess::from_position(Position::from_fen("4k3/8/2P5/8/8/8/8/4K3 w - - 0 1").unwrap());
+
+        assert!(pawn_on_seventh.make_move(&Move::new(Piece::Pawn(Player::White), sq("C6"), sq("C7"))).is_ok());
+        assert_eq!(
+            pawn_on_seventh.make_move(&Move::new(Piece::King(Player::Black), sq("E8"), sq("D8"))),
+            Err(ChessError::KingCompromised {
+                attacker: Piece::Pawn(Player::White),
+                tile: sq("C7")
+            })
+        );
+
+        // a promoting pawn only goes straight to an empty square or takes diagonally
+        let destinations = |fen: &str| Chess::from_position(Position::from_fen(fen).unwrap())
+            .legal_moves()
+            .iter()
+            .filter(|m| m.from == sq("B7") )
+            .map(|m| m.to )
+            .collect::<HashSet<Square>>()
+        ;
+
+        assert_eq!(destinations("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1"), HashSet::from([sq("B8")]));
+        assert_eq!(destinations("1n2k3/1P6/8/8/8/8/8/4K3 w - - 0 1"), HashSet::new());
+        assert_eq!(destinations("rn2k3/1P6/8/8/8/8/8/4K3 w - - 0 1"), HashSet::from([sq("A8")]));
+        assert_eq!(test_chess.legal_moves().iter().filter(|m| m.from == sq("B7") ).count(), 4);
+
+        // and a pawn short of the seventh rank can't jump onto the last one
+        let short = Chess::from_position(Position::from_fen("4k3/8/1P6/8/8/8/8/4K3 w - - 0 1").unwrap());
+
+        assert_eq!(short.legal_moves_from(&sq("B6")).iter().map(|m| m.to ).collect::<Vec<Square>>(), vec![sq("B7")]);
+
+        // nor does a pawn push two squares onto a piece
+        let blocked = Chess::from_position(Position::from_fen("4k3/8/8/8/4n3/8/4P3/4K3 w - - 0 1").unwrap());
+
+        assert_eq!(blocked.legal_moves_from(&sq("E2")).iter().map(|m| m.to ).collect::<Vec<Square>>(), vec![sq("E3")]);
+    }
+
+    #[test]
+    fn parse_errors() {
+
+        let test_chess = Chess::new();
+
+        let parse = |input: &str| Move::from_input(&test_chess, input.to_string()).unwrap_err();
+
+        assert_eq!(parse("E2 X9\n"), ChessError::UnableToParseInput {
+            notation: Notation::Move,
+            offset: 3,
+            found: String::from("X9")
+        });
+        assert_eq!(parse("  E2\n"), ChessError::UnableToParseInput {
+            notation: Notation::Move,
+            offset: 4,
+            found: String::new()
+        });
+        assert_eq!(parse("E2 E4 E5"), ChessError::UnableToParseInput {
+            notation: Notation::Move,
+            offset: 6,
+            found: String::from("E5")
+        });
+        assert_eq!(parse("E4 E5"), ChessError::EmptyTile {
+            tile: sq("E4")
+        });
+
+        let boxed: Box<dyn std::error::Error> = Box::new(parse("E2 X9"));
+        assert_eq!(boxed.to_string(), "Can't understand move; unexpected 'X9' at offset 3.");
+
+        // a move of a PGN file is pointed at in the file, also when written together with its number
+        let games = parse_pgn("1.e4 e5 2. Nf9 Nc6").unwrap();
+        let e = Game::from_pgn(&games[0]).unwrap_err();
+
+        assert_eq!(games[0].moves[..3], [(2, String::from("e4")), (5, String::from("e5")), (11, String::from("Nf9"))]);
+        assert_eq!(e.to_string(), "Can't understand PGN; unexpected 'Nf9' at offset 11.");
+    }
+
+    #[test]
+    fn squares() {
+
+        let e4 = sq("e4");
+
+        assert_eq!(e4, sq("E4"));
+        assert_eq!(e4, Square::new(File::E, Rank::FOURTH));
+        assert_eq!(e4.to_string(), "E4");
+        assert!("E9".parse::<Square>().is_err());
+        assert!("e".parse::<Square>().is_err());
+
+        assert_eq!(e4.offset((1, -1)), Some(sq("D5")));
+        assert_eq!(sq("A1").offset((0, -1)), None);
+        assert_eq!(sq("H8").offset((1, 0)), None);
+        assert_eq!(sq("G1").offset_to(&sq("F3")), (2, -1));
+
+        assert_eq!(Square::all().count(), 64);
+        assert_eq!(Square::all().next(), Some(sq("A1")));
+        assert_eq!(Square::all().last(), Some(sq("H8")));
+        assert_eq!(Piece::Knight(Player::White).possible_moves(&sq("A1")).len(), 2);
+    }
+
+    #[test]
+    fn positions() {
+
+        let test_chess = play(&["E2 E4", "E7 E5", "G1 F3"]).chess;
+
+        assert_eq!(test_chess.position.halfmove_clock, 1);
+        assert_eq!(test_chess.position.fullmove_number, 2);
+        assert_eq!(test_chess.position.turn, Player::Black);
+
+        // the same position reached through another move order
+        let a = play(&["G1 F3", "B8 C6", "B1 C3"]).chess.position;
+        let b = play(&["B1 C3", "B8 C6", "G1 F3"]).chess.position;
+
+        assert_eq!(a, b);
+        assert_eq!(HashSet::from([a, b, Position::new()]).len(), 2);
+    }
+
+    #[test]
+    fn castling_rights() {
+
+        let setup = |pieces: &[(&str, Piece)]| {
+            let mut position = Position::new();
+            position.board = [[None; 8]; 8];
+            for (code, piece) in pieces {
+                position.board[sq(code)] = Some(*piece);
+            }
+            Game::from_position(position)
+        };
+        let castle = |test_game: &mut Game, to: &str| test_game.make_move(&Move::new(Piece::King(test_game.chess.position.turn), sq("E1"), sq(to)));
+
+        let mut test_game = setup(&[
+            ("E1", Piece::King(Player::White)),
+            ("A1", Piece::Rook(Player::White)),
+            ("H1", Piece::Rook(Player::White)),
+            ("C8", Piece::King(Player::Black)),
+            ("G3", Piece::Knight(Player::Black))
+        ]);
+
+        for input in ["A1 A2", "G3 H1"] {
+            let m = Move::from_input(&test_game.chess, String::from(input)).unwrap();
+            assert!(test_game.make_move(&m).is_ok());
+        }
+
+        assert!(!test_game.chess.position.castling.white_king_side);
+        assert!(!test_game.chess.position.castling.white_queen_side);
+        assert!(test_game.chess.position.castling.black_king_side);
+        assert_eq!(
+            castle(&mut test_game, "C1").unwrap_err().to_string(),
+            "Castling move is no longer available; the rook on A1 has moved."
+        );
+        assert_eq!(
+            castle(&mut test_game, "G1").unwrap_err().to_string(),
+            "Castling move is no longer available; the rook on H1 was captured."
+        );
+
+        // no castling through or out of a check
+        let pieces = [
+            ("E1", Piece::King(Player::White)),
+            ("A1", Piece::Rook(Player::White)),
+            ("H1", Piece::Rook(Player::White)),
+            ("A8", Piece::King(Player::Black))
+        ];
+
+        let mut test_game = setup(&[&pieces[..], &[("F5", Piece::Rook(Player::Black))]].concat());
+        assert_eq!(castle(&mut test_game, "G1"), Err(ChessError::CastlingPathIsCompromised {
+            attacker: Piece::Rook(Player::Black),
+            from: sq("F5"),
+            tile: sq("F1")
+        }));
+        assert!(castle(&mut test_game, "C1").is_ok());
+
+        let mut test_game = setup(&[&pieces[..], &[("E2", Piece::Pawn(Player::Black))]].concat());
+        assert!(castle(&mut test_game, "C1").is_err());
+
+        let mut test_game = setup(&[&pieces[..], &[("B3", Piece::Knight(Player::Black))]].concat());
+        assert!(castle(&mut test_game, "G1").is_ok());
+    }
+
+    #[test]
+    fn en_passant_target() {
+
+        let try_moves = |test_chess: &mut Chess, inputs: &[&str]| inputs
+            .iter()
+            .map(|input| {
+                let m = Move::from_input(test_chess, input.to_string())?;
+                test_chess.make_move(&m)
+            })
+            .collect::<Result<Vec<ChessState>, ChessError>>()
+        ;
+
+        let mut test_chess = Chess::new();
+
+        assert!(try_moves(&mut test_chess, &["E2 E4", "A7 A6", "E4 E5", "D7 D5"]).is_ok());
+        assert_eq!(test_chess.position.en_passant, Some(sq("D6")));
+        assert!(try_moves(&mut test_chess, &["E5 D6"]).is_ok());
+        assert_eq!(test_chess.position.en_passant, None);
+        assert_eq!(test_chess.position.board[sq("D5")], None);
+
+        // the pawn beside came one square at a time
+        let mut test_chess = Chess::new();
+
+        assert!(try_moves(&mut test_chess, &["E2 E4", "D7 D6", "E4 E5", "D6 D5"]).is_ok());
+        assert_eq!(test_chess.position.en_passant, None);
+        assert!(matches!(try_moves(&mut test_chess, &["E5 D6"]), Err(ChessError::NotAllowedMove { .. })));
+
+        // the chance is gone after another move
+        let mut test_chess = Chess::new();
+
+        assert!(try_moves(&mut test_chess, &["E2 E4", "A7 A6", "E4 E5", "D7 D5", "H2 H3", "H7 H6"]).is_ok());
+        assert!(try_moves(&mut test_chess, &["E5 D6"]).is_err());
+
+        // a pawn that gives check by its two-square push may be taken en passant
+        let mut test_chess = Chess::from_position(Position::from_fen("4k3/3p4/8/4P3/4K3/8/8/8 b - - 0 1").unwrap());
+        let en_passant = Move::new(Piece::Pawn(Player::White), sq("E5"), sq("D6"));
+
+        assert!(matches!(try_moves(&mut test_chess, &["D7 D5"]).as_deref(), Ok([ChessState::Check { .. }])));
+        assert!(test_chess.legal_moves().contains(&en_passant));
+        assert_eq!(test_chess.make_move(&en_passant), Ok(ChessState::Normal));
+        assert_eq!(test_chess.position.board[sq("D5")], None);
+    }
+
+    #[test]
+    fn polyglot_book() {
+
+        // the keys the Polyglot specification gives for these positions
+        let test_chess = Chess::new();
+        let key = test_chess.position.polyglot_key();
+
+        assert_eq!(key, 0x463b96181691fc9c);
+
+        let mut played = Chess::new();
+        for (input, expected) in [
+            ("E2 E4", 0x823c9b50fd114196),
+            ("D7 D5", 0x0756b94461c50fb0),
+            ("E4 E5", 0x662fafb965db29d4),
+            ("F7 F5", 0x22a48b5a8e47ff78),
+            ("E1 E2", 0x652a607ca3f242c1),
+            ("E8 F7", 0x00fdd303c946bdd9)
+        ] {
+            let m = Move::from_input(&played, String::from(input)).unwrap();
+            assert!(played.make_move(&m).is_ok());
+            assert_eq!(played.position.polyglot_key(), expected);
+        }
+
+        // e2e4 and d2d4 with weights 3 and 1, and an entry of another position
+        let entry = |key: u64, raw_move: u16, weight: u16| [
+            &key.to_be_bytes()[..],
+            &raw_move.to_be_bytes()[..],
+            &weight.to_be_bytes()[..],
+            &0u32.to_be_bytes()[..]
+        ].concat();
+        let bytes = [entry(key, 731, 1), entry(key, 796, 3), entry(key.wrapping_add(1), 796, 5)].concat();
+
+        let path = std::env::temp_dir().join(format!("cli_chess_book_{}.bin", std::process::id()));
+        std::fs::write(&path, bytes).unwrap();
+        let book = Book::open(&path).unwrap();
+        let mut engine = Engine::new();
+        engine.set_book(Some(Book::open(&path).unwrap()));
+        std::fs::remove_file(&path).unwrap();
+
+        let moves: Vec<(String, u16)> = book
+            .moves(&test_chess)
+            .iter()
+            .map(|(m, w)| (m.to_san(&test_chess), *w) )
+            .collect()
+        ;
+        assert_eq!(moves, vec![(String::from("e4"), 3), (String::from("d4"), 1)]);
+
+        assert_eq!(book.pick_with(&test_chess, 2).map(|m| m.to), Some(sq("E4")));
+        assert_eq!(book.pick_with(&test_chess, 3).map(|m| m.to), Some(sq("D4")));
+
+        // the engine plays from the book without searching, until the game leaves it
+        let result = engine.search(&test_chess, &SearchLimits::default());
+        assert!(result.best_move.is_some_and(|m| [sq("E4"), sq("D4")].contains(&m.to)));
+        assert_eq!(result.nodes, 0);
+
+        let result = engine.search(&played, &SearchLimits {
+            depth: Some(1),
+            ..SearchLimits::default()
+        });
+        assert!(result.nodes > 0);
+    }
+
+    #[test]
+    fn opening_classification() {
+
+        assert_eq!(Game::new().opening(), None);
+
+        let italian = play(&["E2 E4", "E7 E5", "G1 F3", "B8 C6", "F1 C4"]);
+        assert_eq!(italian.opening().unwrap().to_string(), "C50 Italian Game");
+
+        // later moves keep the most specific opening reached
+        let two_knights = play(&["E2 E4", "E7 E5", "G1 F3", "B8 C6", "F1 C4", "G8 F6", "D2 D3", "F8 E7"]);
+        assert_eq!(two_knights.opening().unwrap().eco, "C55");
+
+        let mate = play(&[&SCHOLARS_MATE[..], &["H5 F7"]].concat());
+        let pgn = mate.to_pgn();
+
+        assert!(pgn.contains("[Result \"1-0\"]\n"));
+        assert!(pgn.contains("[ECO \"C23\"]\n[Opening \"Bishop's Opening\"]\n"));
+        assert!(pgn.ends_with("\n\n1. e4 e5 2. Bc4 Nc6 3. Qh5 Nf6 4. Qxf7# 1-0\n"));
+        assert_eq!(italian.result, GameResult::Unfinished);
+    }
+
+    #[test]
+    fn engine_search() {
+
+        let test_chess = play(&SCHOLARS_MATE).chess;
+
+        let engine = Engine::new();
+        let result = engine.search(&test_chess, &SearchLimits {
+            depth: Some(3),
+            ..SearchLimits::default()
+        });
+
+        assert_eq!(result.best_move.map(|m| m.to_san(&test_chess)), Some(String::from("Qxf7#")));
+        assert!(result.is_mate());
+        assert_eq!(result.depth, 1);
+
+        // a mate that only a pinned knight could block
+        let pinned = Chess::from_position(Position::from_fen("7k/7p/5n1P/4B3/8/8/8/K2R4 w - - 0 1").unwrap());
+        let m = Move::new(Piece::Rook(Player::White), sq("D1"), sq("D8"));
+
+        let result = engine.search(&pinned, &SearchLimits {
+            depth: Some(1),
+            ..SearchLimits::default()
+        });
+        assert_eq!(result.best_move, Some(m));
+        assert_eq!(result.score, MATE - 1);
+
+        let budget = TimeBudget::allocate(&TimeControl {
+            remaining: Duration::from_secs(60),
+            increment: Duration::from_secs(1),
+            moves_to_go: None
+        });
+        assert!(budget.soft > Duration::from_secs(2) && budget.soft < Duration::from_secs(3));
+        assert!(budget.hard > budget.soft && budget.hard < Duration::from_secs(60));
+
+        // the last move of the time control may use everything but the overhead
+        let budget = TimeBudget::allocate(&TimeControl {
+            remaining: Duration::from_secs(1),
+            increment: Duration::ZERO,
+            moves_to_go: Some(1)
+        });
+        assert!(budget.soft == budget.hard && budget.hard < Duration::from_secs(1));
+
+        // an unlimited search ends when it is stopped from another thread
+        let stop = engine.stop_handle();
+        let stopper = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(200));
+            stop.store(true, Ordering::Relaxed);
+        });
+        let result = engine.search(&Chess::new(), &SearchLimits::default());
+        stopper.join().unwrap();
+
+        assert!(result.best_move.is_some());
+    }
+
+    #[test]
+    fn transposition_table() {
+
+        let table = TranspositionTable::new(1);
+        let key = Position::new().key();
+        let entry = |depth: u32, score: i32| TtEntry {
+            key,
+            depth,
+            bound: Bound::Exact,
+            score,
+            best_move: None
+        };
+
+        assert!(table.capacity() > 1000);
+        assert_eq!(table.probe(key), None);
+
+        table.store(entry(4, 10));
+        table.store(entry(2, 20));
+        assert_eq!(table.probe(key), Some(entry(4, 10)));
+
+        table.store(entry(5, 30));
+        assert_eq!(table.probe(key), Some(entry(5, 30)));
+
+        // the move, bound and a negative score come back as they were stored
+        let promotion = TtEntry {
+            key,
+            depth: 7,
+            bound: Bound::Upper,
+            score: -MATE + 3,
+            best_move: Some(Move {
+                promotion: Some(Piece::Knight(Player::Black)),
+                ..Move::new(Piece::Pawn(Player::Black), sq("B2"), sq("A1"))
+            })
+        };
+        table.store(promotion);
+        assert_eq!(table.probe(key), Some(promotion));
+        assert_eq!(table.probe(key.wrapping_add(table.capacity() as u64)), None);
+
+        table.clear();
+        assert_eq!(table.probe(key), None);
+
+        // the move counters don't change the key
+        let mut counted = Position::new();
+        counted.halfmove_clock = 3;
+        counted.fullmove_number = 7;
+        assert_eq!(counted.key(), key);
+
+        // a second search of the same position is cut short by what the first one stored
+        let engine = Engine::with_hash_size(1);
+        let limits = SearchLimits {
+            depth: Some(3),
+            ..SearchLimits::default()
+        };
+        let first = engine.search(&Chess::new(), &limits);
+        let second = engine.search(&Chess::new(), &limits);
+
+        assert_eq!(first.score, second.score);
+        assert!(second.nodes < first.nodes);
+    }
+
+    #[test]
+    fn static_exchange() {
+
+        let mut test_chess = Chess::new();
+        test_chess.position.board = [[None; 8]; 8];
+        test_chess.position.castling = CastlingRights::none();
+        [
+            ("E1", Piece::King(Player::White)),
+            ("E8", Piece::King(Player::Black)),
+            ("D1", Piece::Queen(Player::White)),
+            ("B3", Piece::Bishob(Player::White)),
+            ("C3", Piece::Knight(Player::White)),
+            ("D5", Piece::Pawn(Player::Black)),
+            ("E6", Piece::Pawn(Player::Black)),
+            ("H5", Piece::Rook(Player::Black))
+        ]
+            .iter()
+            .for_each(|(code, piece)| test_chess.position.board[sq(code)] = Some(*piece) )
+        ;
+
+        let capture = |from: &str, to: &str| {
+            let m = Move::from_input(&test_chess, format!("{} {}", from, to)).unwrap();
+            test_chess.see(&m)
+        };
+
+        // the rook recaptures only when nothing can take it back
+        assert_eq!(capture("C3", "D5"), 1 - 3 + 1);
+        assert_eq!(capture("B3", "D5"), 1 - 3 + 1);
+        assert_eq!(capture("D1", "D5"), 1 - 9 + 1);
+
+        // nothing defends the rook
+        assert_eq!(capture("D1", "H5"), 5);
+
+        assert!(test_chess.noisy_moves().iter().all(|m| test_chess.is_capture(m) ));
+        assert_eq!(test_chess.noisy_moves().len(), 4);
+
+        // the engine sees the knight it would lose after taking the pawn
+        let result = Engine::new().search(&test_chess, &SearchLimits {
+            depth: Some(1),
+            ..SearchLimits::default()
+        });
+        assert_eq!(result.best_move.map(|m| m.to), Some(sq("H5")));
+    }
+
+    #[test]
+    fn uci_and_threads() {
+
+        let mut uci = Uci::new(Engine::new());
+
+        assert!(uci.handle("position startpos moves e2e4 e7e5 g1f3 b8c6 f1c4"));
+        assert_eq!(uci.chess().position.board[sq("C4")], Some(Piece::Bishob(Player::White)));
+        assert_eq!(uci.chess().position.turn, Player::Black);
+
+        // a position from a FEN, with the moves played from it
+        assert!(uci.handle("position fen 4k3/8/8/8/8/8/4P3/4K3 w - - 0 1 moves e2e4 e8d7"));
+        assert_eq!(uci.chess().position.board[sq("E4")], Some(Piece::Pawn(Player::White)));
+        assert_eq!(uci.chess().position.board[sq("D7")], Some(Piece::King(Player::Black)));
+        assert_eq!(uci.chess().position.turn, Player::White);
+
+        // a stop right after go ends the search, and so does an option set during one
+        assert!(uci.handle("go infinite"));
+        assert!(uci.handle("stop"));
+        assert!(uci.handle("go infinite"));
+        assert!(uci.handle("setoption name Threads value 2"));
+        assert!(!uci.handle("quit"));
+
+        let test_chess = Chess::new();
+        assert!(test_chess.move_from_uci("e2e5").is_err());
+        assert!(test_chess.move_from_uci("e2").is_err());
+        assert_eq!(test_chess.move_from_uci("g1f3").map(|m| m.to_san(&test_chess)), Ok(String::from("Nf3")));
+
+        // the helper threads share the table, and the result is still the main thread's
+        let mut engine = Engine::new();
+        engine.set_threads(3);
+        assert_eq!(engine.threads(), 3);
+
+        let result = engine.search(&test_chess, &SearchLimits {
+            depth: Some(2),
+            ..SearchLimits::default()
+        });
+
+        assert_eq!(result.depth, 2);
+        assert!(result.best_move.is_some());
+    }
+
+    #[test]
+    fn evaluation_tuning() {
+
+        let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        assert_eq!(Position::from_fen(start), Ok(Position::new()));
+        assert_eq!(Position::new().to_fen(), start);
+
+        let italian = "r1bqk1nr/pppp1ppp/2n5/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4";
+        assert_eq!(Position::from_fen(italian).unwrap().to_fen(), italian);
+        assert!(matches!(
+            Position::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNX w KQkq -"),
+            Err(ChessError::UnableToParseInput { notation: Notation::Fen, offset: 42, .. })
+        ));
+
+        // the evaluation is symmetrical and counts material
+        let params = EvalParams::default();
+        assert_eq!(params.evaluate(&Position::new()), 0);
+        let mut queenless = Position::new();
+        queenless.board[sq("D8")] = None;
+        assert!(params.evaluate(&queenless) > 800);
+
+        let pgn = r#"[Event "Casual"]
+[Result "1-0"]
+
+1. e4 e5 2. Nf3 {the usual} Nc6 (2... d6 3. d4) 3. Bc4 Bc5 4. 0-0 Nf6 $1
+5. d3 d6 6. c3 O-O 7. h3 h6 1-0
+
+[Result "0-1"]
+1.f3 e5 2.g4 Qh4# 0-1
+"#;
+        let games = parse_pgn(pgn).unwrap();
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].tag("Event"), Some("Casual"));
+        assert_eq!(games[0].moves.len(), 14);
+        assert_eq!(games[1].result.as_deref(), Some("0-1"));
+
+        // the first game has positions after its opening, the second one is over too soon
+        let positions = read_pgn(pgn).unwrap();
+        assert_eq!(positions.len(), 7);
+        assert!(positions.iter().all(|p| p.result == 1.0 ));
+
+        let epd = format!("{} c9 \"1-0\";\n{} [0.5]\n", queenless.to_fen(), start);
+        let labeled = read_epd(&epd).unwrap();
+        assert_eq!(labeled.iter().map(|p| p.result ).collect::<Vec<f64>>(), vec![1.0, 0.5]);
+        assert!(read_epd(start).is_err());
+
+        let mut tuner = Tuner::new([labeled, positions].concat());
+        let mut params = EvalParams::default();
+        let error = tuner.fit_scale(&params);
+        let tuned = tuner.tune_pass(&mut params, error);
+
+        assert!(tuned < error);
+        assert_eq!(tuner.error(&params), tuned);
+        assert_eq!(params.values_mut().len(), 405);
+    }
+
+    #[test]
+    fn difficulty_levels() {
+
+        assert_eq!(Difficulty::from_name("Beginner"), Some(Difficulty::Beginner));
+        assert_eq!(Difficulty::from_name("grandmaster"), None);
+        assert_eq!(Difficulty::Intermediate.to_string(), "Intermediate");
+        assert_eq!(Difficulty::Master.skill(), None);
+        assert!(Difficulty::all().windows(2).all(|pair| match (pair[0].skill(), pair[1].skill()) {
+            (Some(weaker), Some(stronger)) => weaker.depth < stronger.depth && weaker.noise > stronger.noise,
+            _ => true
+        }));
+
+        let test_chess = play(&SCHOLARS_MATE).chess;
+
+        // a mate in one is found at every level that never blunders
+        for difficulty in [Difficulty::Advanced, Difficulty::Master] {
+            let mut engine = Engine::new();
+            engine.set_difficulty(difficulty);
+            let result = engine.search(&test_chess, &SearchLimits::default());
+            assert_eq!(result.best_move.map(|m| m.to_san(&test_chess)), Some(String::from("Qxf7#")));
+        }
+
+        // the beginner searches shallowly and picks the same moves again from the same seed
+        let mut engine = Engine::new();
+        engine.set_difficulty(Difficulty::Beginner);
+        let picks: Vec<Vec<Option<Move>>> = (0..2)
+            .map(|_| {
+                engine.set_seed(7);
+                (0..10)
+                    .map(|_| {
+                        let result = engine.search(&Chess::new(), &SearchLimits::default());
+                        assert_eq!(result.depth, 1);
+                        result.best_move
+                    })
+                    .collect()
+            })
+            .collect()
+        ;
+        assert_eq!(picks[0], picks[1]);
+        assert!(picks[0].iter().all(|m| m.is_some_and(|m| Chess::new().legal_moves().contains(&m)) ));
+
+        // even a blunder doesn't walk into a mate when there's another way out
+        let moves = Chess::new().legal_moves();
+        let scores: Vec<(Move, i32)> = moves
+            .iter()
+            .enumerate()
+            .map(|(i, m)| (*m, if i == 0 { 0 } else { 2 - MATE }) )
+            .collect()
+        ;
+        let skill = Difficulty::Beginner.skill().map(|skill| Skill {
+            blunder_chance: 1.0,
+            ..skill
+        });
+        let mut state = 1u64;
+        for _ in 0..20 {
+            let pick = skill.and_then(|skill| skill.pick(&scores, || {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                state >> 33
+            }));
+            assert_eq!(pick, Some((moves[0], 0)));
+        }
+    }
 }
\ No newline at end of file
@@ -1,7 +1,3 @@
-use std::{
-    io,
-    collections::HashMap
-};
 use crate::{
     chess::{
         Chess,
@@ -9,10 +5,24 @@ use crate::{
     },
     piece::Piece,
     player::Player,
-    error::ChessError
+    square::{
+        Square,
+        File,
+        Rank
+    },
+    position::{
+        Position,
+        CastlingSide
+    },
+    error::{
+        ChessError,
+        Notation
+    }
 };
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum MoveType {
     Castle,
     EnPassant,
@@ -30,10 +40,7 @@ impl MoveType {
     ) -> Self {
 
         let moves = m.piece.moves();
-        let dif = (
-            m.to.0 as i8 - m.from.0 as i8,
-            m.to.1 as i8 - m.from.1 as i8
-        );
+        let dif = m.from.offset_to(&m.to);
 
         match m.piece {
             Piece::King(_) => if moves[8..=9].contains(&dif) {
@@ -41,18 +48,17 @@ impl MoveType {
             } else {
                 MoveType::Other
             },
-            Piece::Pawn(p) => if &p == &Player::White && m.to.0 == 7 {
-                MoveType::Promotion
-            } else if &p == &Player::Black && m.to.0 == 0 {
+            // a double step never promotes, it is only allowed from the start rank
+            Piece::Pawn(p) => if moves[3] == dif {
+                MoveType::PawnTwo
+            } else if p == Player::White && m.to.rank() == Rank::EIGHTH || p == Player::Black && m.to.rank() == Rank::FIRST {
                 MoveType::Promotion
             } else if moves[1..=2].contains(&dif) {
-                if let None = chess.board[m.to.0][m.to.1] {
+                if chess.position.board[m.to].is_none() {
                     MoveType::EnPassant
                 } else {
                     MoveType::PawnEat
                 }
-            } else if moves[3] == dif {
-                MoveType::PawnTwo
             } else {
                 MoveType::PawnOne
             },
@@ -62,81 +68,147 @@ impl MoveType {
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Move {
     pub piece: Piece,
-    pub from: (usize, usize),
-    pub to: (usize, usize)
+    pub from: Square,
+    pub to: Square,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub promotion: Option<Piece>
 }
 
 impl Move {
 
+    // a move that isn't a promotion, or one promoting to a queen
+    pub fn new(
+        piece: Piece,
+        from: Square,
+        to: Square
+    ) -> Move {
+
+        Move {
+            piece,
+            from,
+            to,
+            promotion: None
+        }
+    }
+
     pub fn from_input(chess: &Chess, input: String) -> Result<Self, ChessError> {
 
-        let err = Err(ChessError::UnableToParseInput);
-
-        match input.trim().split_whitespace().collect::<Vec<&str>>().as_slice() {
-            [from, to] => match (Move::get_index_codes().get(*from), Move::get_index_codes().get(*to)) {
-                (Some(from), Some(to)) => {
-                    let piece = match chess.find_piece(from) {
-                        Some(p) => p,
-                        None => return Err(ChessError::EmptyTile)
-                    };
-                    Ok(Move {
-                        piece,
-                        from: *from,
-                        to: *to
+        // tokens are paired with their byte offset in the input to point at what went wrong
+        let tokens: Vec<(usize, &str)> = input
+            .split_whitespace()
+            .map(|t| (t.as_ptr() as usize - input.as_ptr() as usize, t) )
+            .collect()
+        ;
+        let err = |(offset, found): (usize, &str)| ChessError::UnableToParseInput {
+            notation: Notation::Move,
+            offset,
+            found: found.to_string()
+        };
+        let end = (input.trim_end().len(), "");
+
+        let square = |(offset, code): (usize, &str)| code
+            .parse::<Square>()
+            .map_err(|_| err((offset, code)) )
+        ;
+
+        match tokens.as_slice() {
+            [from, to] => {
+                let from = square(*from)?;
+                let to = square(*to)?;
+                let piece = match chess.position.find_piece(&from) {
+                    Some(p) => p,
+                    None => return Err(ChessError::EmptyTile {
+                        tile: from
                     })
-                },
-                _ => err
+                };
+                Ok(Move::new(piece, from, to))
+            },
+            [from] => {
+                square(*from)?;
+                Err(err(end))
             },
-            _ => err
+            [] => Err(err(end)),
+            [_, _, extra, ..] => Err(err(*extra))
         }
     }
 
-    fn get_index_codes() -> HashMap<String, (usize, usize)> {
-
-        Move::get_codes().into_iter().zip(Move::get_indexes().into_iter()).collect()
-    }
-
-    fn get_codes() -> Vec<String> {
-
-        let letters = ["A", "B", "C", "D", "E", "F", "G", "H"];
-        let nums = ["1", "2", "3", "4", "5", "6", "7", "8"];
-
-        letters
-            .iter()
-            .fold(Vec::new(), |mut acc, l| {
-
-                nums.into_iter().for_each(|n| acc.push( l.to_string() + n ) );
-
-                acc
-            })
-    }
-
-    fn get_indexes() -> Vec<(usize, usize)> {
-
-        (0..8)
-            .fold(Vec::new(), |mut acc, y| {
-
-                (0..8).for_each(|x| acc.push((x, y)) );
+    pub fn to_san(&self, chess: &Chess) -> String {
 
-                acc
-            })
-    }
+        let code = |square: Square| square.to_string().to_lowercase();
+        let letter = |piece: &Piece| match piece {
+            Piece::Pawn(_) => "",
+            Piece::Bishob(_) => "B",
+            Piece::Knight(_) => "N",
+            Piece::Rook(_) => "R",
+            Piece::Queen(_) => "Q",
+            Piece::King(_) => "K"
+        };
 
-    pub fn is_within_board(&self) -> bool {
+        let move_type = MoveType::determine_type(chess, self);
+        let capture = chess.position.board[self.to].is_some() || move_type == MoveType::EnPassant;
 
-        let check = |(x, y): (&usize, &usize)| {
-            if (0..8).contains(x) && (0..8).contains(y) {
-                true
+        let mut san = match (&move_type, self.piece) {
+            (MoveType::Castle, _) => if self.to.file() == File::G {
+                String::from("O-O")
             } else {
-                false
+                String::from("O-O-O")
+            },
+            (_, Piece::Pawn(_)) => if capture {
+                format!("{}x{}", &code(self.from)[..1], code(self.to))
+            } else {
+                code(self.to)
+            },
+            (_, piece) => {
+                // other pieces of the same kind that could also move to the destination
+                let rivals: Vec<Square> = Square::all()
+                    .filter(|square| *square != self.from && chess.position.board[*square] == Some(piece) )
+                    .filter(|square| chess
+                        .legal_moves_from(square)
+                        .iter()
+                        .any(|m| m.to == self.to )
+                    )
+                    .collect()
+                ;
+
+                let from = code(self.from);
+                let disambiguation = if rivals.is_empty() {
+                    ""
+                } else if rivals.iter().all(|s| s.file() != self.from.file() ) {
+                    &from[..1]
+                } else if rivals.iter().all(|s| s.rank() != self.from.rank() ) {
+                    &from[1..]
+                } else {
+                    &from[..]
+                };
+
+                format!(
+                    "{}{}{}{}",
+                    letter(&piece),
+                    disambiguation,
+                    if capture { "x" } else { "" },
+                    code(self.to)
+                )
             }
         };
 
-        [&self.from, &self.to]
-            .iter()
-            .all(|(x, y)| check((x, y)) == true )
+        if move_type == MoveType::Promotion {
+            let promotion = self.promotion.unwrap_or(Piece::Queen(chess.position.turn));
+            if !matches!(promotion, Piece::Pawn(_)) {
+                san.push('=');
+                san.push_str(letter(&promotion));
+            }
+        }
+
+        match chess.clone().make_move(self) {
+            Ok(ChessState::Checkmate) => san.push('#'),
+            Ok(ChessState::Check { .. }) => san.push('+'),
+            _ => ()
+        }
+
+        san
     }
 
     pub fn process_move(
@@ -145,20 +217,18 @@ impl Move {
         test: Option<&Player>
     ) -> Result<ChessState, ChessError> {
 
-        if let Err(e) = self.regular_tests(chess, test) {
-            return Err(e)
-        }
+        self.regular_tests(chess, test)?;
 
-        let move_type = match self.specific_tests(chess) {
-            Ok(mt) => mt,
-            Err(e) => return Err(e)
-        };
+        let move_type = self.specific_tests(chess)?;
 
-        if let Some(_) = chess.is_check(&chess.turn.opponent(), Some(self)) {
-            return Err(ChessError::KingCompromised)
+        if let Some(checking_moves) = chess.is_check(&chess.position.turn.opponent(), Some(self)) {
+            return Err(ChessError::KingCompromised {
+                attacker: checking_moves[0].piece,
+                tile: checking_moves[0].from
+            })
         }
 
-        if let Some(_) = test {
+        if test.is_some() {
             return Ok(ChessState::Normal)
         }
 
@@ -171,12 +241,8 @@ impl Move {
         player_to_move: Player
     ) -> bool {
 
-        if let Some(piece) = chess.board[self.to.0][self.to.1] {
-            if Piece::get_pieces(&player_to_move.opponent()).contains(&piece) {
-                true
-            } else {
-                false
-            }
+        if let Some(piece) = chess.position.board[self.to] {
+            Piece::get_pieces(&player_to_move.opponent()).contains(&piece)
         } else {
             true
         }
@@ -188,9 +254,7 @@ impl Move {
         test: Option<&Player>
     ) -> Result<MoveType, ChessError> {
 
-        if let Err(e) = self.regular_tests(chess, test) {
-            return Err(e)
-        }
+        self.regular_tests(chess, test)?;
 
         self.specific_tests(chess)
     }
@@ -201,36 +265,59 @@ impl Move {
         test: Option<&Player>
     )-> Result<(), ChessError> {
 
-        let mut player_to_move = chess.turn;
+        let mut player_to_move = chess.position.turn;
 
         if let Some(p) = test {
             player_to_move = *p;
         }
 
         if Player::find_player(&self.piece) != player_to_move {
-            return Err(ChessError::PieceBelongsToOpponent)
+            return Err(ChessError::PieceBelongsToOpponent {
+                tile: self.from
+            })
         }
 
-        if let false = self.is_valid_destination(chess, player_to_move) {
-            return Err(ChessError::InvalidDestination)
+        if !self.is_valid_destination(chess, player_to_move) {
+            return Err(ChessError::InvalidDestination {
+                tile: self.to
+            })
         }
 
-        if let false = self.piece.possible_moves(&self.from).contains(&self) {
-            return Err(ChessError::NotAllowedMove)
+        // promotion choice doesn't change where the piece may move
+        let m = Move {
+            promotion: None,
+            ..*self
+        };
+
+        if !self.piece.possible_moves(&self.from).contains(&m) {
+            return Err(ChessError::NotAllowedMove {
+                attempted: *self
+            })
         }
 
         if let ChessState::Check {
             checked_player: _,
             moves_left
         } = &chess.state {
-            if let false = moves_left.contains(&self) {
-                return Err(ChessError::NotAllowedMoveInCheck)
+            // promotions are listed once for each piece
+            if !moves_left.iter().any(|l| l.from == m.from && l.to == m.to ) {
+                return Err(ChessError::NotAllowedMoveInCheck {
+                    attempted: *self
+                })
             }
         }
 
-        if self.piece != Piece::Knight(chess.turn) {
-            if let false = chess.path_is_clear(&self.from, &self.to) {
-                return  Err(ChessError::PathIsBlocked);
+        if self.piece != Piece::Knight(chess.position.turn) {
+            let blocker = chess
+                .position.find_path(&self.from, &self.to)
+                .into_iter()
+                .find_map(|square| chess.position.board[square].map(|p| (p, square)) )
+            ;
+            if let Some((blocker, tile)) = blocker {
+                return Err(ChessError::PathIsBlocked {
+                    blocker,
+                    tile
+                })
             }
         }
 
@@ -242,57 +329,102 @@ impl Move {
         chess: &Chess
     )-> Result<MoveType, ChessError> {
 
-        let move_type = MoveType::determine_type(chess, &self);
+        let move_type = MoveType::determine_type(chess, self);
 
         let result = match move_type {
             MoveType::Castle => self.castling_tests(chess),
             MoveType::EnPassant => self.en_passant_tests(chess),
             MoveType::PawnEat => self.pawn_eat_tests(chess),
-            MoveType::PawnTwo => self.pawn_two_tests(),
+            MoveType::PawnTwo => self.pawn_two_tests(chess),
             MoveType::PawnOne => self.pawn_one_tests(chess),
+            // the pawn gets to the last rank the way it moves anywhere else
+            MoveType::Promotion => if self.from.file() == self.to.file() {
+                self.pawn_one_tests(chess)
+            } else {
+                self.pawn_eat_tests(chess)
+            }.and_then(|_| self.promotion_tests() ),
             _ => Ok(())
         };
 
-        if let Err(e) = result {
-            return Err(e)
-        }
+        result?;
 
         Ok(move_type)
     }
 
+    fn promotion_tests(&self) -> Result<(), ChessError> {
+
+        let player = Player::find_player(&self.piece);
+
+        match self.promotion {
+            None => Ok(()),
+            Some(Piece::Knight(p) | Piece::Bishob(p) | Piece::Rook(p) | Piece::Queen(p)) if p == player => Ok(()),
+            Some(piece) => Err(ChessError::InvalidPromotion {
+                piece
+            })
+        }
+    }
+
     fn castling_tests(
         &self,
         chess: &Chess
     ) -> Result<(), ChessError> {
 
-        let (king_from, rook_from) = match chess.turn {
-            Player::White => if self.to == (0, 2) {
-                ((0, 4), (0, 0))
-            } else {
-                ((0, 4), (0, 7))
-            },
-            Player::Black => if self.to == (7, 2) {
-                ((7, 4), (7, 0))
-            } else {
-                ((7, 4), (7, 7))
-            }
+        let player = Player::find_player(&self.piece);
+        let (rank, side) = match (player, self.to.file()) {
+            (Player::White, File::C) => (Rank::FIRST, CastlingSide::QueenSide),
+            (Player::White, _) => (Rank::FIRST, CastlingSide::KingSide),
+            (Player::Black, File::C) => (Rank::EIGHTH, CastlingSide::QueenSide),
+            (Player::Black, _) => (Rank::EIGHTH, CastlingSide::KingSide)
+        };
+        // the rook's home square and the square the king passes over
+        let (rook_file, passed_file) = match side {
+            CastlingSide::QueenSide => (File::A, File::D),
+            CastlingSide::KingSide => (File::H, File::F)
         };
+        let king_from = Square::new(File::E, rank);
+        let rook_from = Square::new(rook_file, rank);
+        let passed = Square::new(passed_file, rank);
 
         if self.from != king_from {
-            return Err(ChessError::NotAllowedMove)
+            return Err(ChessError::NotAllowedMove {
+                attempted: *self
+            })
         }
-        
-        if let true = chess.moves
-            .iter()
-            .any(|m| m.from == king_from || m.from == rook_from ) {
-            
-            return Err(ChessError::CastlingMoveUnavailable)
+
+        if !chess.position.castling.has(&player, &side) {
+            // the game fills in the move that gave the right up from its history
+            return Err(ChessError::CastlingMoveUnavailable {
+                rook: rook_from,
+                lost_by: None
+            })
         }
 
-        let castling_path = chess.find_path(&king_from, &rook_from);
+        let blocker = chess.position
+            .find_path(&king_from, &rook_from)
+            .into_iter()
+            .find_map(|square| chess.position.board[square].map(|p| (p, square)) )
+        ;
+        if let Some((blocker, tile)) = blocker {
+            return Err(ChessError::PathIsBlocked {
+                blocker,
+                tile
+            })
+        }
 
-        if let Some(_) = chess.piece_can_move_to(&chess.turn.opponent(), castling_path, false) {
-            return Err(ChessError::CastlingPathIsCompromised)
+        // the king may not castle out of, through or into a check
+        for tile in [king_from, passed, self.to] {
+            let attack = chess.position
+                .attackers(&tile, &player.opponent())
+                .into_iter()
+                .find_map(|from| chess.position.board[from].map(|p| (p, from)) )
+            ;
+            if let Some((attacker, from)) = attack {
+                return Err(ChessError::CastlingPathIsCompromised {
+                    attacker,
+                    from,
+                    tile
+                })
+            }
         }
 
         Ok(())
@@ -303,17 +435,10 @@ impl Move {
         chess: &Chess
     ) -> Result<(), ChessError> {
 
-        let opp_pawn = Piece::Pawn(chess.turn.opponent());
-        let adjacent_tiles = [(self.to.0 - 1, self.to.1), (self.to.0 + 1, self.to.1)];
-
-        if let false = adjacent_tiles.into_iter().any(|(x, y)| {
-            if let Some(last_move) = chess.moves.last() {
-                chess.board[x][y] == Some(opp_pawn) && last_move.to == (x, y)
-            } else {
-                false
-            }
-        }) {
-            return Err(ChessError::NotAllowedMove)
+        if chess.position.en_passant != Some(self.to) {
+            return Err(ChessError::NotAllowedMove {
+                attempted: *self
+            })
         }
 
         Ok(())
@@ -324,24 +449,29 @@ impl Move {
         chess: &Chess
     ) -> Result<(), ChessError> {
 
-        if chess.board[self.to.0][self.to.1] == None {
-            return Err(ChessError::InvalidDestination)
+        if chess.position.board[self.to].is_none() {
+            return Err(ChessError::InvalidDestination {
+                tile: self.to
+            })
         }
 
         Ok(())
     }
 
     fn pawn_two_tests(
-        &self
+        &self,
+        chess: &Chess
     ) -> Result<(), ChessError> {
 
         let player = Player::find_player(&self.piece);
 
-        if player == Player::White && self.from.0 != 1 || player == Player::Black && self.from.0 != 6 {
-            return Err(ChessError::NotAllowedMove)
+        if player == Player::White && self.from.rank() != Rank::SECOND || player == Player::Black && self.from.rank() != Rank::SEVENTH {
+            return Err(ChessError::NotAllowedMove {
+                attempted: *self
+            })
         }
 
-        Ok(())
+        self.pawn_one_tests(chess)
     }
 
     fn pawn_one_tests(
@@ -349,8 +479,10 @@ impl Move {
         chess: &Chess
     ) -> Result<(), ChessError> {
 
-        if chess.board[self.to.0][self.to.1] != None {
-            return Err(ChessError::NotAllowedMove)
+        if chess.position.board[self.to].is_some() {
+            return Err(ChessError::NotAllowedMove {
+                attempted: *self
+            })
         }
 
         Ok(())
@@ -362,75 +494,70 @@ impl Move {
         move_type: &MoveType
     ) -> ChessState {
 
-        let mut new_piece = None;
+        chess.position.castling.update(&self.from);
+        chess.position.castling.update(&self.to);
+
+        chess.position.en_passant = match move_type {
+            MoveType::PawnTwo => self.from.offset((self.from.offset_to(&self.to).0 / 2, 0)),
+            _ => None
+        };
+
+        // the fifty-move count starts over on captures and pawn moves
+        if matches!(self.piece, Piece::Pawn(_)) || chess.position.board[self.to].is_some() {
+            chess.position.halfmove_clock = 0;
+        } else {
+            chess.position.halfmove_clock += 1;
+        }
 
         match move_type {
-            MoveType::Promotion => {
-                println!("Your pawn reached the last row.");
-                loop {
-                    println!("Choose the piece you want to change it to - 'QUEEN', 'ROOK', 'BISHOB', 'KNIGHT' - or 'NONE' if no change.");
-                
-                    let mut s = String::new();
-                    io::stdin().read_line(&mut s).expect("Failed to read line");
-
-                    new_piece = match s.trim().as_ref() {
-                        "QUEEN" => Some(Piece::Queen(chess.turn)),
-                        "ROOK" => Some(Piece::Rook(chess.turn)),
-                        "BISHOB" => Some(Piece::Bishob(chess.turn)),
-                        "KNIGHT" => Some(Piece::King(chess.turn)),
-                        "NONE" => Some(Piece::Pawn(chess.turn)),
-                        _ => {
-                            println!("Invalid input, try again.");
-                            continue
-                        }
-                    };
-                    break
-                }
-            },
             MoveType::Castle => {
-                let (rook_from, rook_to) = match self.to {
-                    (0, 2) => ((0, 0), (0, 3)),
-                    (0, 6) => ((0, 7), (0, 5)),
-                    (7, 2) => ((7, 0), (7, 3)),
-                    _ => ((7, 7), (7, 5))
+                let rank = self.to.rank();
+                let (rook_from, rook_to) = if self.to.file() == File::C {
+                    (Square::new(File::A, rank), Square::new(File::D, rank))
+                } else {
+                    (Square::new(File::H, rank), Square::new(File::F, rank))
                 };
-                chess.board[rook_from.0][rook_from.1] = None;
-                chess.board[rook_to.0][rook_to.1] = Some(Piece::Rook(chess.turn));
+                chess.position.board[rook_from] = None;
+                chess.position.board[rook_to] = Some(Piece::Rook(chess.position.turn));
             },
             MoveType::EnPassant => {
-                let opp_pawn_pos = if self.to.0 > self.from.0 {
-                    (self.to.0 - 1,self.to.1)
-                } else {
-                    (self.to.0 + 1, self.to.1)
-                };
-                chess.board[opp_pawn_pos.0][opp_pawn_pos.1] = None;
+                // the captured pawn stands beside the moving one, on the destination file
+                let opp_pawn_pos = Square::new(self.to.file(), self.from.rank());
+                chess.position.board[opp_pawn_pos] = None;
             },
             _ => ()
         }
 
         if *move_type == MoveType::Promotion {
-            chess.board[self.from.0][self.from.1] = None;
-            chess.board[self.to.0][self.to.1] = new_piece;
+            chess.position.board[self.from] = None;
+            chess.position.board[self.to] = Some(self.promotion.unwrap_or(Piece::Queen(chess.position.turn)));
         } else {
-            chess.board[self.from.0][self.from.1] = None;
-            chess.board[self.to.0][self.to.1] = Some(self.piece);
+            chess.position.board[self.from] = None;
+            chess.position.board[self.to] = Some(self.piece);
         }
 
-        chess.moves.push(*self);
+        let opponent = chess.position.turn.opponent();
+
+        if !chess.position.is_attacked(&chess.position.find_king(&opponent), &chess.position.turn) {
+            return ChessState::Normal
+        }
 
-        let is_check = chess.is_check(&chess.turn, None);
+        // the replies are those of the opponent, who isn't held to this side's state
+        let moves_left = Chess {
+            position: Position {
+                turn: opponent,
+                ..chess.position
+            },
+            state: ChessState::Normal
+        }.legal_moves();
 
-        if let Some(moves_left) = chess.moves_left(is_check) {
-            if moves_left.is_empty() {
-                ChessState::Checkmate
-            } else {
-                ChessState::Check {
-                    checked_player: chess.turn.opponent(),
-                    moves_left
-                }
-            }
+        if moves_left.is_empty() {
+            ChessState::Checkmate
         } else {
-            ChessState::Normal
+            ChessState::Check {
+                checked_player: opponent,
+                moves_left
+            }
         }
     }
 }
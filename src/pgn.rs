@@ -0,0 +1,296 @@
+use std::{
+    fs,
+    io,
+    mem,
+    path::Path
+};
+use crate::{
+    chess::{
+        Chess,
+        ChessState
+    },
+    r#move::Move,
+    game::{
+        Game,
+        GameResult
+    },
+    position::Position,
+    error::{
+        ChessError,
+        Notation
+    }
+};
+
+// movetext lines are kept below the 80 columns the PGN standard asks for
+const LINE_WIDTH: usize = 79;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    // moves in SAN with their byte offset in the text, without move numbers and annotations
+    pub moves: Vec<(usize, String)>,
+    pub result: Option<String>
+}
+
+impl PgnGame {
+    pub fn tag(&self, name: &str) -> Option<&str> {
+
+        self.tags
+            .iter()
+            .find(|(n, _)| n == name )
+            .map(|(_, value)| value.as_str() )
+    }
+}
+
+// the games of a PGN file; comments, variations and annotation glyphs are skipped
+pub fn parse_pgn(text: &str) -> Result<Vec<PgnGame>, ChessError> {
+
+    let err = |offset: usize| ChessError::UnableToParseInput {
+        notation: Notation::Pgn,
+        offset,
+        found: text[offset..].split_whitespace().next().unwrap_or_default().to_string()
+    };
+    // the offset of the closing character, counted from the start of the text
+    let closing = |from: usize, c: char| text[from..].find(c).map(|i| from + i );
+
+    let mut games = Vec::new();
+    let mut game = PgnGame::default();
+    let mut offset = 0;
+
+    while let Some(c) = text[offset..].chars().next() {
+
+        let start = offset;
+        offset += c.len_utf8();
+
+        match c {
+            c if c.is_whitespace() => (),
+            '[' => {
+                // a tag after the movetext starts the next game
+                if !game.moves.is_empty() {
+                    games.push(mem::take(&mut game));
+                }
+                let end = closing(start, ']').ok_or(err(start))?;
+                let (name, value) = text[start + 1..end]
+                    .trim()
+                    .split_once(char::is_whitespace)
+                    .ok_or(err(start))?
+                ;
+                let value = value
+                    .trim()
+                    .strip_prefix('"')
+                    .and_then(|v| v.strip_suffix('"') )
+                    .ok_or(err(start))?
+                ;
+                game.tags.push((name.to_string(), value.replace("\\\"", "\"").replace("\\\\", "\\")));
+                offset = end + 1;
+            },
+            '{' => offset = closing(start, '}').ok_or(err(start))? + 1,
+            ';' => offset = closing(start, '\n').unwrap_or(text.len()),
+            '(' => {
+                let mut depth = 1;
+                while depth > 0 {
+                    match text[offset..].chars().next() {
+                        Some('(') => depth += 1,
+                        Some(')') => depth -= 1,
+                        Some('{') => offset = closing(offset, '}').ok_or(err(start))?,
+                        Some(_) => (),
+                        None => return Err(err(start))
+                    }
+                    offset += text[offset..].chars().next().map(|c| c.len_utf8() ).unwrap_or(0);
+                }
+            },
+            _ => {
+                let end = text[start..]
+                    .find(|c: char| c.is_whitespace() || "[]{}();".contains(c) )
+                    .map(|i| start + i )
+                    .unwrap_or(text.len())
+                ;
+                offset = end;
+
+                let token = &text[start..end];
+                // move numbers may be written together with the move, e.g. "1.e4"
+                let san = if token.contains('.') {
+                    token.trim_start_matches(|c: char| c.is_ascii_digit() ).trim_start_matches('.')
+                } else {
+                    token
+                };
+
+                match token {
+                    "1-0" | "0-1" | "1/2-1/2" | "*" => {
+                        game.result = Some(token.to_string());
+                        games.push(mem::take(&mut game));
+                    },
+                    _ if token.starts_with('$') => (),
+                    _ if san.is_empty() => (),
+                    _ => game.moves.push((san.as_ptr() as usize - text.as_ptr() as usize, san.trim_end_matches(['!', '?']).to_string()))
+                }
+            }
+        }
+    }
+
+    if !game.moves.is_empty() || !game.tags.is_empty() {
+        games.push(game);
+    }
+
+    Ok(games)
+}
+
+impl ChessError {
+    // a SAN that can't be read is pointed at where it stands in the PGN text
+    pub fn in_pgn(self, offset: usize) -> ChessError {
+
+        match self {
+            ChessError::UnableToParseInput {
+                found,
+                ..
+            } => ChessError::UnableToParseInput {
+                notation: Notation::Pgn,
+                offset,
+                found
+            },
+            e => e
+        }
+    }
+}
+
+impl Chess {
+    // a move given in SAN; castling may also be written with zeros
+    pub fn move_from_san(&self, san: &str) -> Result<Move, ChessError> {
+
+        let normalise = |san: &str| {
+            let san = san.trim_end_matches(['+', '#', '!', '?']).replace('=', "");
+            if san.starts_with("0-0") { san.replace('0', "O") } else { san }
+        };
+        let wanted = normalise(san);
+
+        self
+            .legal_moves()
+            .into_iter()
+            .filter(|m| wanted.starts_with("O-O") || wanted.contains(&m.to.to_string().to_lowercase()) )
+            .find(|m| normalise(&m.to_san(self)) == wanted )
+            .ok_or(ChessError::UnableToParseInput {
+                notation: Notation::Move,
+                offset: 0,
+                found: san.to_string()
+            })
+    }
+
+    pub fn play_san(&mut self, san: &str) -> Result<ChessState, ChessError> {
+
+        let m = self.move_from_san(san)?;
+
+        self.make_move(&m)
+    }
+}
+
+impl Game {
+    // the game of a PGN record, set up from its FEN tag if it has one
+    pub fn from_pgn(pgn: &PgnGame) -> Result<Game, ChessError> {
+
+        let mut game = match pgn.tag("FEN") {
+            Some(fen) => Game::from_position(Position::from_fen(fen)?),
+            None => Game::new()
+        };
+
+        for (offset, san) in &pgn.moves {
+            game.play_san(san).map_err(|e| e.in_pgn(*offset) )?;
+        }
+
+        game.tags = pgn.tags.clone();
+        game.result = match pgn.result.as_deref().or(pgn.tag("Result")) {
+            Some("1-0") => GameResult::WhiteWon,
+            Some("0-1") => GameResult::BlackWon,
+            Some("1/2-1/2") => GameResult::Draw,
+            _ => game.result
+        };
+
+        Ok(game)
+    }
+
+    // the first game of a PGN file
+    pub fn load_pgn<P: AsRef<Path>>(path: P) -> io::Result<Game> {
+
+        let text = fs::read_to_string(path)?;
+        let invalid = |e: ChessError| io::Error::new(io::ErrorKind::InvalidData, e);
+
+        let pgn = parse_pgn(&text)
+            .map_err(invalid)?
+            .into_iter()
+            .next()
+            .ok_or(io::Error::new(io::ErrorKind::InvalidData, "the file has no games"))?
+        ;
+
+        Game::from_pgn(&pgn).map_err(invalid)
+    }
+
+    pub fn play_san(&mut self, san: &str) -> Result<ChessState, ChessError> {
+
+        let m = self.chess.move_from_san(san)?;
+
+        self.make_move(&m)
+    }
+
+    pub fn to_pgn(&self) -> String {
+
+        let result = self.result.to_string();
+
+        // the seven tag roster comes first, in its order
+        let roster = [
+            ("Event", "?"),
+            ("Site", "?"),
+            ("Date", "????.??.??"),
+            ("Round", "?"),
+            ("White", "?"),
+            ("Black", "?")
+        ];
+        let mut tags: Vec<(&str, String)> = roster
+            .iter()
+            .map(|(name, unknown)| (*name, self.tag(name).unwrap_or(unknown).to_string()) )
+            .collect()
+        ;
+        tags.push(("Result", result.clone()));
+
+        if self.start != Position::new() {
+            tags.push(("SetUp", "1".to_string()));
+            tags.push(("FEN", self.start.to_fen()));
+        }
+
+        if let Some(opening) = self.opening() {
+            tags.push(("ECO", opening.eco.to_string()));
+            tags.push(("Opening", opening.name.to_string()));
+        }
+
+        let written: Vec<&str> = tags.iter().map(|(name, _)| *name ).collect();
+        tags.extend(self.tags
+            .iter()
+            .filter(|(name, _)| !written.contains(&name.as_str()) )
+            .map(|(name, value)| (name.as_str(), value.clone()) )
+        );
+
+        let mut pgn: String = tags
+            .iter()
+            .map(|(name, value)| format!("[{} \"{}\"]\n", name, value.replace('\\', "\\\\").replace('"', "\\\"")) )
+            .collect()
+        ;
+        pgn.push('\n');
+
+        let mut line = String::new();
+
+        for token in self.numbered_moves().iter().flat_map(|m| m.split(' ') ).chain([result.as_str()]) {
+            if !line.is_empty() && line.len() + 1 + token.len() > LINE_WIDTH {
+                pgn.push_str(&line);
+                pgn.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(token);
+        }
+
+        pgn.push_str(&line);
+        pgn.push('\n');
+
+        pgn
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
     player::Player,
     r#move::Move,
+    square::Square,
     constant::{
         STRAIGHTS,
         DIAGONALS
@@ -8,8 +9,11 @@ use crate::{
 };
 
 #[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", content = "player", rename_all = "lowercase"))]
 pub enum Piece {
     Pawn(Player),
+    #[cfg_attr(feature = "serde", serde(rename = "bishop"))]
     Bishob(Player),
     Knight(Player),
     Rook(Player),
@@ -49,25 +53,28 @@ impl Piece {
 
     pub fn possible_moves(
         &self,
-        (x, y): &(usize, usize)
+        from: &Square
     ) -> Vec<Move> {
 
         let moves = self.moves();
 
         moves
             .iter()
-            .map(|m| {
-                let to = (*x as i8 + m.0, *y as i8 + m.1);
-                Move {
-                    piece: *self,
-                    from: (*x, *y),
-                    to: (to.0 as usize, to.1 as usize)
-                }
-            })
-            .filter(|m| m.is_within_board() )
+            .filter_map(|m| from.offset(*m) )
+            .map(|to| Move::new(*self, *from, to) )
             .collect()
     }
 
+    pub fn value(&self) -> u32 {
+        match self {
+            Piece::Pawn(_) => 1,
+            Piece::Bishob(_) | Piece::Knight(_) => 3,
+            Piece::Rook(_) => 5,
+            Piece::Queen(_) => 9,
+            Piece::King(_) => 0
+        }
+    }
+
     pub fn get_pieces(p: &Player) -> [Piece; 6] {
 
         match p {
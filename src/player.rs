@@ -1,6 +1,8 @@
 use crate::piece::Piece;
 
 #[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum Player {
     White,
     Black
@@ -16,7 +18,7 @@ impl Player {
 
     pub fn find_player(piece: &Piece) -> Player {
 
-        if let true = Piece::get_pieces(&Player::White).contains(piece) {
+        if Piece::get_pieces(&Player::White).contains(piece) {
             Player::White
         } else {
             Player::Black
@@ -0,0 +1,392 @@
+use std::hash::{
+    DefaultHasher,
+    Hash,
+    Hasher
+};
+use crate::{
+    r#move::Move,
+    piece::Piece,
+    player::Player,
+    square::{
+        Square,
+        File,
+        Rank
+    },
+    constant::{
+        INIT_BOARD,
+        DIAGONALS,
+        STRAIGHTS
+    }
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CastlingSide {
+    KingSide,
+    QueenSide
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CastlingRights {
+    pub white_king_side: bool,
+    pub white_queen_side: bool,
+    pub black_king_side: bool,
+    pub black_queen_side: bool
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Position {
+    pub board: [[Option<Piece>; 8]; 8],
+    pub turn: Player,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub castling: CastlingRights,
+    // the square a pawn passed over with a two-square push on the last move
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub en_passant: Option<Square>,
+    // moves since the last capture or pawn move, for the fifty-move rule
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub halfmove_clock: u32,
+    #[cfg_attr(feature = "serde", serde(default = "Position::first_move"))]
+    pub fullmove_number: u32
+}
+
+impl Default for CastlingRights {
+    fn default() -> Self {
+
+        CastlingRights {
+            white_king_side: true,
+            white_queen_side: true,
+            black_king_side: true,
+            black_queen_side: true
+        }
+    }
+}
+
+impl CastlingRights {
+    pub fn none() -> CastlingRights {
+
+        CastlingRights {
+            white_king_side: false,
+            white_queen_side: false,
+            black_king_side: false,
+            black_queen_side: false
+        }
+    }
+
+    pub fn has(
+        &self,
+        player: &Player,
+        side: &CastlingSide
+    ) -> bool {
+
+        match (player, side) {
+            (Player::White, CastlingSide::KingSide) => self.white_king_side,
+            (Player::White, CastlingSide::QueenSide) => self.white_queen_side,
+            (Player::Black, CastlingSide::KingSide) => self.black_king_side,
+            (Player::Black, CastlingSide::QueenSide) => self.black_queen_side
+        }
+    }
+
+    // a move from or to one of the king and rook home squares gives up the rights tied to it
+    pub fn update(&mut self, square: &Square) {
+
+        match (square.file(), square.rank()) {
+            (File::E, Rank::FIRST) => {
+                self.white_king_side = false;
+                self.white_queen_side = false;
+            },
+            (File::H, Rank::FIRST) => self.white_king_side = false,
+            (File::A, Rank::FIRST) => self.white_queen_side = false,
+            (File::E, Rank::EIGHTH) => {
+                self.black_king_side = false;
+                self.black_queen_side = false;
+            },
+            (File::H, Rank::EIGHTH) => self.black_king_side = false,
+            (File::A, Rank::EIGHTH) => self.black_queen_side = false,
+            _ => ()
+        }
+    }
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Position {
+    pub fn new() -> Position {
+
+        Position {
+            board: INIT_BOARD,
+            turn: Player::White,
+            castling: CastlingRights::default(),
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: Position::first_move()
+        }
+    }
+
+    fn first_move() -> u32 {
+        1
+    }
+
+    // a hash of what decides the moves from here on, leaving out the move counters
+    pub fn key(&self) -> u64 {
+
+        let mut hasher = DefaultHasher::new();
+
+        (self.board, self.turn, self.castling, self.en_passant).hash(&mut hasher);
+
+        hasher.finish()
+    }
+
+    pub fn find_path(
+        &self,
+        from: &Square,
+        to: &Square
+    ) -> Vec<Square> {
+
+        let m = from.offset_to(to);
+
+        [DIAGONALS, STRAIGHTS]
+            .iter()
+            .flatten()
+            .filter(|a| a.contains(&m) )
+            .flatten()
+            .take_while(|i| *i != &m )
+            .filter_map(|m| from.offset(*m) )
+            .collect()
+    }
+
+    pub fn path_is_clear(
+        &self,
+        from: &Square,
+        to: &Square
+    ) -> bool {
+
+        self.find_path(from, to)
+         .iter()
+         .all(|square| self.board[*square].is_none() )
+    }
+
+    pub fn find_king(
+        &self,
+        player: &Player
+    ) -> Square {
+
+        Square::all()
+            .fold(Square::default(), |acc, square| if self.board[square] == Some(Piece::King(*player)) {
+                square
+            } else {
+                acc
+            })
+    }
+
+    // material on the board of White minus that of Black
+    pub fn material_balance(&self) -> i32 {
+
+        self.board
+            .iter()
+            .flatten()
+            .flatten()
+            .map(|p| match Player::find_player(p) {
+                Player::White => p.value() as i32,
+                Player::Black => -(p.value() as i32)
+            })
+            .sum()
+    }
+
+    pub fn find_piece(
+        &self,
+        tile: &Square
+    ) -> Option<Piece> {
+
+        if let Some(piece) = self.board[*tile] {
+
+            let players = [Player::White, Player::Black];
+
+            players
+                .iter()
+                .fold(None, |mut acc, player|{
+
+                    if let Some(p) = Piece::get_pieces(player).iter().find(|p| *p == &piece ) {
+                        acc = Some(*p);
+                    }
+
+                    acc
+                })
+        } else {
+            None
+        }
+    }
+
+    // squares of the player's pieces that attack the given square
+    pub fn attackers(
+        &self,
+        square: &Square,
+        player: &Player
+    ) -> Vec<Square> {
+
+        self.attacks(*square, *player).collect()
+    }
+
+    pub fn is_attacked(
+        &self,
+        square: &Square,
+        player: &Player
+    ) -> bool {
+
+        self.attacks(*square, *player).next().is_some()
+    }
+
+    // the attackers are found one at a time, so looking for any stops at the first
+    fn attacks(
+        &self,
+        square: Square,
+        player: Player
+    ) -> impl Iterator<Item = Square> + '_ {
+
+        let at = move |offset: (i8, i8), piece: Piece| square
+            .offset(offset)
+            .filter(|s| self.board[*s] == Some(piece) )
+        ;
+
+        // an attacking pawn stands one rank behind the square from its own side
+        let pawn_rank = match player {
+            Player::White => -1,
+            Player::Black => 1
+        };
+
+        let pawns = [(pawn_rank, -1), (pawn_rank, 1)]
+            .into_iter()
+            .filter_map(move |o| at(o, Piece::Pawn(player)) )
+        ;
+        let knights = Piece::Knight(player)
+            .moves()
+            .into_iter()
+            .filter_map(move |o| at(o, Piece::Knight(player)) )
+        ;
+        let kings = Piece::King(player).moves()[..8]
+            .to_vec()
+            .into_iter()
+            .filter_map(move |o| at(o, Piece::King(player)) )
+        ;
+
+        // the first piece on each ray is the only one that can reach the square
+        let sliders = [
+            (STRAIGHTS, Piece::Rook(player)),
+            (DIAGONALS, Piece::Bishob(player))
+        ]
+            .into_iter()
+            .flat_map(move |(rays, slider)| rays.into_iter().filter_map(move |ray| ray
+                .iter()
+                .map_while(|o| square.offset(*o) )
+                .find(|s| self.board[*s].is_some() )
+                .filter(|s| [Some(slider), Some(Piece::Queen(player))].contains(&self.board[*s]) )
+            ))
+        ;
+
+        pawns.chain(knights).chain(kings).chain(sliders)
+    }
+
+    // the moves the piece on the square could make, before looking at its own king's safety
+    pub fn piece_moves(
+        &self,
+        from: &Square
+    ) -> Vec<Move> {
+
+        let Some(piece) = self.board[*from] else {
+            return Vec::new()
+        };
+        let player = Player::find_player(&piece);
+        let moves = piece.moves();
+
+        let is_empty = |to: &Square| self.board[*to].is_none();
+        let is_free = |to: &Square| !self.board[*to].is_some_and(|p| Player::find_player(&p) == player );
+
+        // a ray ends at the first piece on it, which is taken if it is the opponent's
+        let slide = |rays: Vec<[(i8, i8); 7]>| rays
+            .into_iter()
+            .flat_map(|ray| {
+                let squares = ray
+                    .iter()
+                    .map_while(|o| from.offset(*o) )
+                    .collect::<Vec<Square>>()
+                ;
+                let reach = squares
+                    .iter()
+                    .position(|s| !is_empty(s) )
+                    .map_or(squares.len(), |i| i + 1)
+                ;
+
+                squares.into_iter().take(reach)
+            })
+            .filter(|to| is_free(to) )
+            .collect::<Vec<Square>>()
+        ;
+
+        let destinations = match piece {
+            Piece::Pawn(_) => {
+                let start = match player {
+                    Player::White => Rank::SECOND,
+                    Player::Black => Rank::SEVENTH
+                };
+                let one = from.offset(moves[0]).filter(|to| is_empty(to) );
+                let eats = moves[1..=2]
+                    .iter()
+                    .filter_map(|o| from.offset(*o) )
+                    .filter(|to| !is_empty(to) && is_free(to) || player == self.turn && self.en_passant == Some(*to) )
+                ;
+                let two = one
+                    .and(from.offset(moves[3]))
+                    .filter(|to| from.rank() == start && is_empty(to) )
+                ;
+
+                one.into_iter().chain(eats).chain(two).collect()
+            },
+            Piece::Knight(_) => moves
+                .iter()
+                .filter_map(|o| from.offset(*o) )
+                .filter(|to| is_free(to) )
+                .collect(),
+            Piece::King(_) => {
+                let rank = match player {
+                    Player::White => Rank::FIRST,
+                    Player::Black => Rank::EIGHTH
+                };
+                // the king may not castle out of, through or into a check
+                let castles = [
+                    (CastlingSide::KingSide, File::H, File::F, File::G),
+                    (CastlingSide::QueenSide, File::A, File::D, File::C)
+                ]
+                    .into_iter()
+                    .filter(|(side, rook_file, passed_file, to_file)| *from == Square::new(File::E, rank)
+                        && self.castling.has(&player, side)
+                        && self.path_is_clear(from, &Square::new(*rook_file, rank))
+                        && [File::E, *passed_file, *to_file]
+                            .iter()
+                            .all(|file| !self.is_attacked(&Square::new(*file, rank), &player.opponent()) )
+                    )
+                    .map(|(_, _, _, to_file)| Square::new(to_file, rank) )
+                ;
+
+                moves[..8]
+                    .iter()
+                    .filter_map(|o| from.offset(*o) )
+                    .filter(|to| is_free(to) )
+                    .chain(castles)
+                    .collect()
+            },
+            Piece::Rook(_) => slide(STRAIGHTS.to_vec()),
+            Piece::Bishob(_) => slide(DIAGONALS.to_vec()),
+            Piece::Queen(_) => slide([DIAGONALS, STRAIGHTS].concat())
+        };
+
+        destinations
+            .into_iter()
+            .map(|to| Move::new(piece, *from, to) )
+            .collect()
+    }
+}
\ No newline at end of file
@@ -0,0 +1,101 @@
+use std::io;
+use crate::{
+    chess::Chess,
+    game::Game,
+    player::Player,
+    display::DisplayOptions
+};
+
+impl Game {
+    pub fn history(&self) -> Vec<(String, Chess)> {
+
+        let mut chess = Chess::from_position(self.start);
+        let mut history = Vec::new();
+
+        for m in &self.moves {
+            let san = m.to_san(&chess);
+            if chess.make_move(m).is_err() {
+                break
+            }
+            history.push((san, chess.clone()));
+        }
+
+        history
+    }
+
+    pub fn numbered_moves(&self) -> Vec<String> {
+
+        let mut numbered: Vec<String> = Vec::new();
+
+        for (ply, san) in self.san.iter().enumerate() {
+            match (self.move_number(ply), numbered.last_mut()) {
+                ((_, Player::Black), Some(pair)) => {
+                    pair.push(' ');
+                    pair.push_str(san);
+                },
+                ((number, Player::Black), None) => numbered.push(format!("{}... {}", number, san)),
+                ((number, Player::White), _) => numbered.push(format!("{}. {}", number, san))
+            }
+        }
+
+        numbered
+    }
+
+    // the move number of a ply counted from the start position, and the player who makes it
+    fn move_number(&self, ply: usize) -> (usize, Player) {
+
+        let ply = ply + (self.start.turn == Player::Black) as usize;
+        let player = if ply.is_multiple_of(2) { Player::White } else { Player::Black };
+
+        (self.start.fullmove_number as usize + ply / 2, player)
+    }
+
+    pub fn replay(&self, options: DisplayOptions) {
+
+        let start = Chess::from_position(self.start);
+        let first = start.position.fullmove_number as usize;
+        let black_first = (self.start.turn == Player::Black) as usize;
+        let history = self.history();
+        let mut ply = 0;
+
+        loop {
+
+            match ply {
+                0 => {
+                    println!("{}", start.display(options));
+                    println!("Start position.");
+                },
+                _ => {
+                    let (san, chess) = &history[ply - 1];
+                    let (number, player) = self.move_number(ply - 1);
+                    let dots = if player == Player::White { "." } else { "..." };
+                    println!("{}", chess.display(options).with_last_move(&self.moves[ply - 1]));
+                    println!("{}{} {}", number, dots, san);
+                }
+            }
+
+            println!("Type 'n' for the next move, 'p' for the previous one, a move number to jump to it or 'q' to stop the replay.");
+
+            let mut input = String::new();
+            if io::stdin().read_line(&mut input).expect("Failed to read line") == 0 {
+                break
+            }
+
+            match input.trim() {
+                "n" | "" => ply = (ply + 1).min(history.len()),
+                "p" => ply = ply.saturating_sub(1),
+                "q" => break,
+                s => match s.parse::<usize>() {
+                    // the ply of White's move with that number
+                    Ok(n) => ply = n
+                        .saturating_add(1)
+                        .saturating_sub(first)
+                        .saturating_mul(2)
+                        .saturating_sub(1 + black_first)
+                        .min(history.len()),
+                    Err(_) => println!("Invalid input, try again.")
+                }
+            }
+        }
+    }
+}
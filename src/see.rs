@@ -0,0 +1,71 @@
+use crate::{
+    chess::Chess,
+    r#move::Move,
+    piece::Piece,
+    player::Player,
+    square::Square
+};
+
+// the king is worth more than anything it could win, so it only recaptures last
+const KING_VALUE: i32 = 100;
+
+fn see_value(piece: &Piece) -> i32 {
+
+    match piece {
+        Piece::King(_) => KING_VALUE,
+        p => p.value() as i32
+    }
+}
+
+impl Chess {
+    // the material the mover ends up with when both sides keep capturing on the
+    // destination with their least valuable piece for as long as it pays off
+    pub fn see(&self, m: &Move) -> i32 {
+
+        let mut position = self.position;
+        let mover = Player::find_player(&m.piece);
+
+        let victim = match position.board[m.to] {
+            Some(piece) => see_value(&piece),
+            None if self.is_capture(m) => Piece::Pawn(mover).value() as i32,
+            None => 0
+        };
+        let piece = m.promotion.unwrap_or(m.piece);
+
+        // the pawn taken en passant doesn't stand on the destination
+        if self.is_capture(m) && position.board[m.to].is_none() {
+            position.board[Square::new(m.to.file(), m.from.rank())] = None;
+        }
+
+        position.board[m.from] = None;
+        position.board[m.to] = Some(piece);
+
+        let mut gains = vec![victim + see_value(&piece) - see_value(&m.piece)];
+        let mut on_square = see_value(&piece);
+        let mut side = mover.opponent();
+
+        while let Some(from) = position
+            .attackers(&m.to, &side)
+            .into_iter()
+            .min_by_key(|s| position.board[*s].map(|p| see_value(&p) ).unwrap_or(KING_VALUE) )
+        {
+            let attacker = position.board[from].unwrap_or(Piece::King(side));
+
+            gains.push(on_square - gains[gains.len() - 1]);
+            on_square = see_value(&attacker);
+
+            position.board[from] = None;
+            position.board[m.to] = Some(attacker);
+            side = side.opponent();
+        }
+
+        // either side may stop capturing when going on would lose material
+        while gains.len() > 1 {
+            let last = gains.pop().unwrap_or(0);
+            let previous = gains.len() - 1;
+            gains[previous] = -(-gains[previous]).max(last);
+        }
+
+        gains[0]
+    }
+}
@@ -0,0 +1,235 @@
+use std::{
+    ops::{
+        Index,
+        IndexMut
+    },
+    str::FromStr
+};
+use crate::{
+    piece::Piece,
+    error::{
+        ChessError,
+        Notation
+    }
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct File(u8);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Rank(u8);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Square {
+    file: File,
+    rank: Rank
+}
+
+impl File {
+    pub const A: File = File(0);
+    pub const B: File = File(1);
+    pub const C: File = File(2);
+    pub const D: File = File(3);
+    pub const E: File = File(4);
+    pub const F: File = File(5);
+    pub const G: File = File(6);
+    pub const H: File = File(7);
+
+    pub fn new(index: usize) -> Option<File> {
+
+        if index < 8 {
+            Some(File(index as u8))
+        } else {
+            None
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        self.0 as usize
+    }
+
+    pub fn offset(&self, delta: i8) -> Option<File> {
+
+        let index = self.0 as i8 + delta;
+
+        if (0..8).contains(&index) {
+            Some(File(index as u8))
+        } else {
+            None
+        }
+    }
+
+    pub fn all() -> impl DoubleEndedIterator<Item = File> {
+        (0..8).map(File)
+    }
+}
+
+impl Rank {
+    pub const FIRST: Rank = Rank(0);
+    pub const SECOND: Rank = Rank(1);
+    pub const THIRD: Rank = Rank(2);
+    pub const FOURTH: Rank = Rank(3);
+    pub const FIFTH: Rank = Rank(4);
+    pub const SIXTH: Rank = Rank(5);
+    pub const SEVENTH: Rank = Rank(6);
+    pub const EIGHTH: Rank = Rank(7);
+
+    pub fn new(index: usize) -> Option<Rank> {
+
+        if index < 8 {
+            Some(Rank(index as u8))
+        } else {
+            None
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        self.0 as usize
+    }
+
+    pub fn offset(&self, delta: i8) -> Option<Rank> {
+
+        let index = self.0 as i8 + delta;
+
+        if (0..8).contains(&index) {
+            Some(Rank(index as u8))
+        } else {
+            None
+        }
+    }
+
+    pub fn all() -> impl DoubleEndedIterator<Item = Rank> {
+        (0..8).map(Rank)
+    }
+}
+
+impl Square {
+    pub fn new(file: File, rank: Rank) -> Square {
+
+        Square {
+            file,
+            rank
+        }
+    }
+
+    pub fn file(&self) -> File {
+        self.file
+    }
+
+    pub fn rank(&self) -> Rank {
+        self.rank
+    }
+
+    // offsets are given as (ranks, files), the way piece moves are listed
+    pub fn offset(&self, (ranks, files): (i8, i8)) -> Option<Square> {
+
+        match (self.file.offset(files), self.rank.offset(ranks)) {
+            (Some(file), Some(rank)) => Some(Square::new(file, rank)),
+            _ => None
+        }
+    }
+
+    // the (ranks, files) offset that leads from this square to another
+    pub fn offset_to(&self, other: &Square) -> (i8, i8) {
+
+        (
+            other.rank.0 as i8 - self.rank.0 as i8,
+            other.file.0 as i8 - self.file.0 as i8
+        )
+    }
+
+    // every square from A1 to H8, rank by rank
+    pub fn all() -> impl Iterator<Item = Square> {
+        Rank::all().flat_map(|rank| File::all().map(move |file| Square::new(file, rank) ) )
+    }
+}
+
+impl FromStr for File {
+    type Err = ChessError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+
+        match s.as_bytes() {
+            [c @ b'a'..=b'h'] => Ok(File(c - b'a')),
+            [c @ b'A'..=b'H'] => Ok(File(c - b'A')),
+            _ => Err(ChessError::UnableToParseInput {
+                notation: Notation::Square,
+                offset: 0,
+                found: s.to_string()
+            })
+        }
+    }
+}
+
+impl FromStr for Rank {
+    type Err = ChessError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+
+        match s.as_bytes() {
+            [c @ b'1'..=b'8'] => Ok(Rank(c - b'1')),
+            _ => Err(ChessError::UnableToParseInput {
+                notation: Notation::Square,
+                offset: 0,
+                found: s.to_string()
+            })
+        }
+    }
+}
+
+impl FromStr for Square {
+    type Err = ChessError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+
+        let err = || ChessError::UnableToParseInput {
+            notation: Notation::Square,
+            offset: 0,
+            found: s.to_string()
+        };
+
+        if s.len() != 2 || !s.is_ascii() {
+            return Err(err())
+        }
+
+        match (s[..1].parse(), s[1..].parse()) {
+            (Ok(file), Ok(rank)) => Ok(Square::new(file, rank)),
+            _ => Err(err())
+        }
+    }
+}
+
+impl Index<Square> for [[Option<Piece>; 8]; 8] {
+    type Output = Option<Piece>;
+
+    fn index(&self, square: Square) -> &Self::Output {
+        &self[square.rank.index()][square.file.index()]
+    }
+}
+
+impl IndexMut<Square> for [[Option<Piece>; 8]; 8] {
+    fn index_mut(&mut self, square: Square) -> &mut Self::Output {
+        &mut self[square.rank.index()][square.file.index()]
+    }
+}
+
+// saved games keep the (rank, file) pairs they were written with
+#[cfg(feature = "serde")]
+impl serde::Serialize for Square {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(&(self.rank.index(), self.file.index()), serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Square {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+
+        let (rank, file): (usize, usize) = serde::Deserialize::deserialize(deserializer)?;
+
+        match (File::new(file), Rank::new(rank)) {
+            (Some(file), Some(rank)) => Ok(Square::new(file, rank)),
+            _ => Err(serde::de::Error::custom("square is outside the board"))
+        }
+    }
+}
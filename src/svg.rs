@@ -0,0 +1,142 @@
+use std::fmt::Write;
+use crate::{
+    chess::Chess,
+    piece::Piece,
+    player::Player,
+    square::{
+        Square,
+        File,
+        Rank
+    },
+    display::Orientation
+};
+
+const TILE: usize = 45;
+const MARGIN: usize = 20;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgOptions {
+    pub orientation: Orientation,
+    pub coordinates: bool,
+    pub highlights: Vec<Square>,
+    pub arrows: Vec<(Square, Square)>
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            orientation: Orientation::White,
+            coordinates: true,
+            highlights: Vec::new(),
+            arrows: Vec::new()
+        }
+    }
+}
+
+impl Chess {
+    pub fn to_svg(&self, options: &SvgOptions) -> String {
+
+        let perspective = match options.orientation {
+            Orientation::White => Player::White,
+            Orientation::Black => Player::Black,
+            Orientation::Turn => self.position.turn
+        };
+
+        // top left corner of a tile in the image
+        let corner = |square: Square| {
+            let (x, y) = (square.rank().index(), square.file().index());
+            match perspective {
+                Player::White => (MARGIN + y * TILE, (7 - x) * TILE),
+                Player::Black => (MARGIN + (7 - y) * TILE, x * TILE)
+            }
+        };
+        let center = |tile: Square| {
+            let (cx, cy) = corner(tile);
+            (cx + TILE / 2, cy + TILE / 2)
+        };
+
+        let size = MARGIN + 8 * TILE;
+        let mut svg = String::new();
+
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{0}" viewBox="0 0 {0} {0}">"#,
+            size
+        );
+        let _ = writeln!(
+            svg,
+            r##"<defs><marker id="arrowhead" markerWidth="4" markerHeight="4" refX="2" refY="2" orient="auto"><path d="M0,0 L4,2 L0,4 z" fill="#15781b"/></marker></defs>"##
+        );
+        let _ = writeln!(svg, r##"<rect width="{0}" height="{0}" fill="#ffffff"/>"##, size);
+
+        for square in Square::all() {
+            let (cx, cy) = corner(square);
+            let fill = if (square.rank().index() + square.file().index()).is_multiple_of(2) { "#b58863" } else { "#f0d9b5" };
+            let _ = writeln!(svg, r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#, cx, cy, TILE, TILE, fill);
+        }
+
+        for tile in &options.highlights {
+            let (cx, cy) = corner(*tile);
+            let _ = writeln!(svg, r##"<rect x="{}" y="{}" width="{}" height="{}" fill="#9bc700" fill-opacity="0.5"/>"##, cx, cy, TILE, TILE);
+        }
+
+        if options.coordinates {
+            for (file, rank) in File::all().zip(Rank::all()) {
+                let (cx, _) = center(Square::new(file, Rank::FIRST));
+                let (_, cy) = center(Square::new(File::A, rank));
+                let _ = writeln!(
+                    svg,
+                    r#"<text x="{}" y="{}" font-family="sans-serif" font-size="12" text-anchor="middle">{}</text>"#,
+                    cx, 8 * TILE + 15, file.to_string().to_lowercase()
+                );
+                let _ = writeln!(
+                    svg,
+                    r#"<text x="{}" y="{}" font-family="sans-serif" font-size="12" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                    MARGIN / 2, cy, rank
+                );
+            }
+        }
+
+        for square in Square::all() {
+            if let Some(piece) = &self.position.board[square] {
+                let (cx, cy) = center(square);
+                let _ = writeln!(svg, "{}", piece_svg(piece, cx, cy));
+            }
+        }
+
+        for (from, to) in &options.arrows {
+            let (x1, y1) = center(*from);
+            let (x2, y2) = center(*to);
+            let _ = writeln!(
+                svg,
+                r##"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="#15781b" stroke-width="8" stroke-opacity="0.8" stroke-linecap="round" marker-end="url(#arrowhead)"/>"##,
+                x1, y1, x2, y2
+            );
+        }
+
+        svg.push_str("</svg>\n");
+
+        svg
+    }
+}
+
+fn piece_svg(
+    piece: &Piece,
+    x: usize,
+    y: usize
+) -> String {
+
+    // the filled black glyphs are drawn for both sides and colored, as the outlined white glyphs can't be filled
+    let (glyph, fill) = match Player::find_player(piece) {
+        Player::White => {
+            let i = Piece::get_pieces(&Player::White).iter().position(|p| p == piece ).unwrap_or(0);
+            (Piece::get_pieces(&Player::Black)[i].unicode(), "#ffffff")
+        },
+        Player::Black => (piece.unicode(), "#000000")
+    };
+
+    format!(
+        r##"<text x="{}" y="{}" font-family="serif" font-size="38" text-anchor="middle" dominant-baseline="central" fill="{}" stroke="#000000" stroke-width="1">{}</text>"##,
+        x, y, fill, glyph
+    )
+}
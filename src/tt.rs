@@ -0,0 +1,209 @@
+use std::{
+    mem,
+    sync::atomic::{
+        AtomicU64,
+        Ordering
+    }
+};
+use crate::{
+    r#move::Move,
+    piece::Piece,
+    player::Player,
+    square::{
+        File,
+        Rank,
+        Square
+    }
+};
+
+pub const DEFAULT_SIZE_MB: usize = 16;
+
+// marks a slot as written, so an empty one never matches a key
+const OCCUPIED: u64 = 1 << 63;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bound {
+    Exact,
+    // the score is at least this, the search failed high
+    Lower,
+    // the score is at most this, no move raised alpha
+    Upper
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TtEntry {
+    pub key: u64,
+    pub depth: u32,
+    pub bound: Bound,
+    pub score: i32,
+    pub best_move: Option<Move>
+}
+
+// the key is kept xored with the data, so a slot torn by two threads writing at once
+// doesn't match either key and is read as empty
+#[derive(Default)]
+struct Slot {
+    key: AtomicU64,
+    data: AtomicU64
+}
+
+// shared by the search threads without a lock
+pub struct TranspositionTable {
+    slots: Vec<Slot>
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        Self::new(DEFAULT_SIZE_MB)
+    }
+}
+
+impl TranspositionTable {
+    pub fn new(size_mb: usize) -> TranspositionTable {
+
+        let capacity = (size_mb * 1024 * 1024 / mem::size_of::<Slot>()).max(1);
+
+        TranspositionTable {
+            slots: (0..capacity).map(|_| Slot::default() ).collect()
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn clear(&self) {
+
+        for slot in &self.slots {
+            slot.key.store(0, Ordering::Relaxed);
+            slot.data.store(0, Ordering::Relaxed);
+        }
+    }
+
+    pub fn probe(&self, key: u64) -> Option<TtEntry> {
+
+        let slot = &self.slots[self.index(key)];
+        let data = slot.data.load(Ordering::Relaxed);
+
+        if data & OCCUPIED == 0 || slot.key.load(Ordering::Relaxed) ^ data != key {
+            return None
+        }
+
+        Some(unpack(key, data))
+    }
+
+    // a deeper search of the same position isn't replaced by a shallower one
+    pub fn store(&self, entry: TtEntry) {
+
+        let slot = &self.slots[self.index(entry.key)];
+
+        if self.probe(entry.key).is_some_and(|old| old.depth > entry.depth ) {
+            return
+        }
+
+        let data = pack(&entry);
+
+        slot.key.store(entry.key ^ data, Ordering::Relaxed);
+        slot.data.store(data, Ordering::Relaxed);
+    }
+
+    fn index(&self, key: u64) -> usize {
+        (key % self.slots.len() as u64) as usize
+    }
+}
+
+// score in bits 0-31, depth in 32-39, bound in 40-41 and the move from bit 42 on
+fn pack(entry: &TtEntry) -> u64 {
+
+    let bound = match entry.bound {
+        Bound::Exact => 0,
+        Bound::Lower => 1,
+        Bound::Upper => 2
+    };
+
+    OCCUPIED
+        | entry.score as u32 as u64
+        | (entry.depth.min(u8::MAX as u32) as u64) << 32
+        | bound << 40
+        | entry.best_move.map(|m| pack_move(&m) ).unwrap_or(0) << 42
+}
+
+fn unpack(key: u64, data: u64) -> TtEntry {
+
+    let bound = match data >> 40 & 3 {
+        0 => Bound::Exact,
+        1 => Bound::Lower,
+        _ => Bound::Upper
+    };
+
+    TtEntry {
+        key,
+        depth: (data >> 32 & 0xff) as u32,
+        bound,
+        score: data as u32 as i32,
+        best_move: unpack_move(data >> 42 & 0x1f_ffff)
+    }
+}
+
+// from and to squares in 6 bits each, then the piece and the promotion choice in 4;
+// a move always has bit 20 set, so 0 stands for no move
+fn pack_move(m: &Move) -> u64 {
+
+    let square = |s: Square| (s.rank().index() * 8 + s.file().index()) as u64;
+
+    1 << 20
+        | square(m.from)
+        | square(m.to) << 6
+        | piece_code(&m.piece) << 12
+        | m.promotion.map(|p| piece_code(&p) ).unwrap_or(0) << 16
+}
+
+fn unpack_move(bits: u64) -> Option<Move> {
+
+    if bits >> 20 == 0 {
+        return None
+    }
+
+    let square = |i: u64| Some(Square::new(File::new(i as usize % 8)?, Rank::new(i as usize / 8)?));
+
+    Some(Move {
+        piece: piece_from_code(bits >> 12 & 0xf)?,
+        from: square(bits & 0x3f)?,
+        to: square(bits >> 6 & 0x3f)?,
+        promotion: piece_from_code(bits >> 16 & 0xf)
+    })
+}
+
+// the kind counts from 1 in the low three bits, and black sets the fourth;
+// 0 is no piece
+fn piece_code(piece: &Piece) -> u64 {
+
+    let kind = match piece {
+        Piece::Pawn(_) => 1,
+        Piece::Bishob(_) => 2,
+        Piece::Knight(_) => 3,
+        Piece::Rook(_) => 4,
+        Piece::Queen(_) => 5,
+        Piece::King(_) => 6
+    };
+
+    match Player::find_player(piece) {
+        Player::White => kind,
+        Player::Black => kind | 8
+    }
+}
+
+fn piece_from_code(code: u64) -> Option<Piece> {
+
+    let player = if code & 8 == 0 { Player::White } else { Player::Black };
+
+    match code & 7 {
+        1 => Some(Piece::Pawn(player)),
+        2 => Some(Piece::Bishob(player)),
+        3 => Some(Piece::Knight(player)),
+        4 => Some(Piece::Rook(player)),
+        5 => Some(Piece::Queen(player)),
+        6 => Some(Piece::King(player)),
+        _ => None
+    }
+}
@@ -0,0 +1,343 @@
+use std::{
+    io::{
+        self,
+        Write
+    },
+    time::{
+        Duration,
+        Instant
+    }
+};
+use crossterm::{
+    cursor::{
+        Hide,
+        MoveTo,
+        Show
+    },
+    event::{
+        self,
+        DisableMouseCapture,
+        EnableMouseCapture,
+        Event,
+        KeyCode,
+        KeyEventKind,
+        MouseButton,
+        MouseEventKind
+    },
+    execute,
+    queue,
+    style::Print,
+    terminal::{
+        self,
+        Clear,
+        ClearType,
+        EnterAlternateScreen,
+        LeaveAlternateScreen
+    }
+};
+use crate::{
+    chess::ChessState,
+    game::Game,
+    r#move::{
+        Move,
+        MoveType
+    },
+    piece::Piece,
+    player::Player,
+    square::Square,
+    error::ChessError,
+    display::DisplayOptions
+};
+
+const PANEL_COLUMN: u16 = 32;
+
+struct RawTerminal;
+
+impl RawTerminal {
+    fn enter() -> io::Result<RawTerminal> {
+
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture, Hide)?;
+
+        Ok(RawTerminal)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+
+        let _ = execute!(io::stdout(), Show, DisableMouseCapture, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+struct Tui {
+    options: DisplayOptions,
+    cursor: Square,
+    selected: Option<Square>,
+    promotion: Option<Move>,
+    message: String,
+    moves: Vec<String>,
+    turn_started: Instant,
+    game_over: bool
+}
+
+impl Tui {
+    fn clock(&self, game: &Game, player: Player) -> Duration {
+
+        let i = player as usize;
+
+        if player == game.chess.position.turn && !self.game_over {
+            game.clocks[i] + self.turn_started.elapsed()
+        } else {
+            game.clocks[i]
+        }
+    }
+
+    fn draw(&self, game: &Game) -> io::Result<()> {
+
+        let mut stdout = io::stdout();
+
+        let options = DisplayOptions {
+            selected: self.selected,
+            cursor: Some(self.cursor),
+            ..self.options
+        };
+        let board = game.display(options).to_string();
+
+        queue!(stdout, Clear(ClearType::All))?;
+
+        for (i, line) in board.lines().enumerate() {
+            queue!(stdout, MoveTo(0, i as u16), Print(line))?;
+        }
+
+        let status = if self.game_over {
+            String::new()
+        } else {
+            format!("{}'s turn. ", game.chess.position.turn)
+        };
+
+        queue!(
+            stdout,
+            MoveTo(0, 10),
+            Print(format!("{}{}", status, self.message)),
+            MoveTo(0, 12),
+            Print("Arrows or mouse: choose tile, Enter/Space: pick, Esc: cancel, Q: quit")
+        )?;
+
+        for (i, line) in self.panel(game).iter().enumerate() {
+            queue!(stdout, MoveTo(PANEL_COLUMN, i as u16), Print(line))?;
+        }
+
+        stdout.flush()
+    }
+
+    fn panel(&self, game: &Game) -> Vec<String> {
+
+        let time = |d: Duration| format!("{:02}:{:02}", d.as_secs() / 60, d.as_secs() % 60);
+
+        let display = game.display(self.options);
+
+        let mut lines = vec![
+            format!("White  {}", time(self.clock(game, Player::White))),
+            format!("Black  {}", time(self.clock(game, Player::Black))),
+            String::new(),
+            format!("Captured by White: {}", display.captured(&Player::White)),
+            format!("Captured by Black: {}", display.captured(&Player::Black)),
+            String::new(),
+            game.opening().map(|o| o.to_string() ).unwrap_or_default(),
+            String::from("Moves")
+        ];
+
+        // only the latest moves fit next to the board
+        let height = terminal::size().map(|(_, h)| h as usize).unwrap_or(24);
+        let room = height.saturating_sub(lines.len()).max(1);
+
+        lines.extend(self.moves.iter().skip(self.moves.len().saturating_sub(room)).cloned());
+
+        lines
+    }
+
+    fn move_cursor(
+        &mut self,
+        game: &Game,
+        (up, right): (i8, i8)
+    ) {
+
+        self.cursor = game.display(self.options).step_cursor(self.cursor, (up, right));
+    }
+
+    fn pick(
+        &mut self,
+        game: &mut Game,
+        tile: Square
+    ) {
+
+        self.cursor = tile;
+        self.message.clear();
+
+        let piece = game.chess.position.find_piece(&tile);
+        let own_piece = piece.is_some_and(|p| Player::find_player(&p) == game.chess.position.turn);
+
+        match (self.selected, piece) {
+            (Some(from), _) if from == tile => { self.selected = None; },
+            (Some(from), _) if !own_piece => self.try_move(game, from, tile),
+            (_, Some(_)) if own_piece => { self.selected = Some(tile); },
+            (_, Some(_)) => self.message = ChessError::PieceBelongsToOpponent {
+                tile
+            }.to_string(),
+            (_, None) => self.message = ChessError::EmptyTile {
+                tile
+            }.to_string()
+        }
+    }
+
+    fn try_move(
+        &mut self,
+        game: &mut Game,
+        from: Square,
+        to: Square
+    ) {
+
+        let piece = match game.chess.position.find_piece(&from) {
+            Some(piece) => piece,
+            None => return
+        };
+
+        let m = Move::new(piece, from, to);
+
+        if MoveType::determine_type(&game.chess, &m) == MoveType::Promotion
+            && m.process_move(&mut game.chess.clone(), Some(&game.chess.position.turn)).is_ok() {
+            self.promotion = Some(m);
+            self.message = String::from("Promote to (Q)ueen, (R)ook, (B)ishob or k(N)ight?");
+            return
+        }
+
+        self.make_move(game, m);
+    }
+
+    fn promote(
+        &mut self,
+        game: &mut Game,
+        code: char
+    ) {
+
+        let piece = match code.to_ascii_uppercase() {
+            'Q' => Piece::Queen(game.chess.position.turn),
+            'R' => Piece::Rook(game.chess.position.turn),
+            'B' => Piece::Bishob(game.chess.position.turn),
+            'N' => Piece::Knight(game.chess.position.turn),
+            _ => return
+        };
+
+        if let Some(m) = self.promotion.take() {
+            self.make_move(game, Move {
+                promotion: Some(piece),
+                ..m
+            });
+        }
+    }
+
+    fn make_move(
+        &mut self,
+        game: &mut Game,
+        m: Move
+    ) {
+
+        let mover = game.chess.position.turn as usize;
+        let elapsed = self.turn_started.elapsed();
+
+        match game.make_move(&m) {
+            Ok(state) => {
+                game.clocks[mover] += elapsed;
+                self.turn_started = Instant::now();
+                self.selected = None;
+                self.moves = game.numbered_moves();
+
+                match state {
+                    ChessState::Checkmate => {
+                        self.game_over = true;
+                        self.message = format!("Checkmate! {} won the game. Press Q to quit.", game.chess.position.turn.opponent());
+                    },
+                    ChessState::Check {
+                        checked_player,
+                        moves_left: _
+                    } => self.message = format!("{} is in a check.", checked_player),
+                    ChessState::Normal => ()
+                }
+            },
+            Err(e) => {
+                self.selected = None;
+                self.message = e.to_string();
+            }
+        }
+    }
+}
+
+impl Game {
+    pub fn play_tui(&mut self, options: DisplayOptions) -> io::Result<()> {
+
+        let _terminal = RawTerminal::enter()?;
+
+        let mut tui = Tui {
+            options: DisplayOptions {
+                colored: true,
+                ..options
+            },
+            cursor: self.chess.position.find_king(&self.chess.position.turn),
+            selected: None,
+            promotion: None,
+            message: String::new(),
+            moves: self.numbered_moves(),
+            turn_started: Instant::now(),
+            game_over: matches!(self.chess.state, ChessState::Checkmate)
+        };
+
+        loop {
+
+            tui.draw(self)?;
+
+            // redraw at least once a second to keep the clocks running
+            if !event::poll(Duration::from_millis(250))? {
+                continue
+            }
+
+            match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                    KeyCode::Char('q') | KeyCode::Char('Q') if tui.promotion.is_none() => break,
+                    _ if tui.game_over => (),
+                    KeyCode::Char(c) if tui.promotion.is_some() => tui.promote(self, c),
+                    KeyCode::Esc => {
+                        tui.promotion = None;
+                        tui.selected = None;
+                        tui.message.clear();
+                    },
+                    _ if tui.promotion.is_some() => (),
+                    KeyCode::Up => tui.move_cursor(self, (1, 0)),
+                    KeyCode::Down => tui.move_cursor(self, (-1, 0)),
+                    KeyCode::Left => tui.move_cursor(self, (0, -1)),
+                    KeyCode::Right => tui.move_cursor(self, (0, 1)),
+                    KeyCode::Enter | KeyCode::Char(' ') => {
+                        let tile = tui.cursor;
+                        tui.pick(self, tile);
+                    },
+                    _ => ()
+                },
+                Event::Mouse(mouse) if mouse.kind == MouseEventKind::Down(MouseButton::Left) => {
+                    let tile = self
+                        .display(tui.options)
+                        .tile_at(mouse.row as usize, mouse.column as usize)
+                    ;
+                    if let Some(tile) = tile {
+                        if !tui.game_over && tui.promotion.is_none() {
+                            tui.pick(self, tile);
+                        }
+                    }
+                },
+                _ => ()
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,224 @@
+use std::thread;
+use crate::{
+    chess::Chess,
+    position::Position,
+    eval::EvalParams,
+    pgn::parse_pgn,
+    error::{
+        ChessError,
+        Notation
+    }
+};
+
+// positions this early in a game are mostly book moves and say little about the evaluation
+const OPENING_PLIES: usize = 8;
+
+// a position with the result of the game it was played in, 1 for a white win and 0 for a black one
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabeledPosition {
+    pub position: Position,
+    pub result: f64
+}
+
+// fits the evaluation to game results by minimising the error of the predicted
+// winning chances, one parameter step at a time
+pub struct Tuner {
+    positions: Vec<LabeledPosition>,
+    scale: f64
+}
+
+// an EPD line labels its position with a 'c9 "1-0";' operation or with "[1.0]" at the end
+pub fn read_epd(text: &str) -> Result<Vec<LabeledPosition>, ChessError> {
+
+    text
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#') )
+        .map(|line| {
+            let start = line.as_ptr() as usize - text.as_ptr() as usize;
+            let position = Position::from_fen(line).map_err(|e| shifted(e, start) )?;
+            let result = label(line).ok_or(ChessError::UnableToParseInput {
+                notation: Notation::Fen,
+                offset: start + line.len(),
+                found: String::new()
+            })?;
+            Ok(LabeledPosition {
+                position,
+                result
+            })
+        })
+        .collect()
+}
+
+// every position of the finished games after the opening, leaving out those in check
+pub fn read_pgn(text: &str) -> Result<Vec<LabeledPosition>, ChessError> {
+
+    let mut positions = Vec::new();
+
+    for game in parse_pgn(text)? {
+
+        let Some(result) = game.result.as_deref().or(game.tag("Result")).and_then(result_value) else {
+            continue
+        };
+
+        let mut chess = match game.tag("FEN") {
+            Some(fen) => Chess::from_position(Position::from_fen(fen)?),
+            None => Chess::new()
+        };
+
+        for (ply, (offset, san)) in game.moves.iter().enumerate() {
+
+            chess.play_san(san).map_err(|e| e.in_pgn(*offset) )?;
+
+            let turn = chess.position.turn;
+            let in_check = chess.position.is_attacked(&chess.position.find_king(&turn), &turn.opponent());
+
+            if ply + 1 >= OPENING_PLIES && !in_check {
+                positions.push(LabeledPosition {
+                    position: chess.position,
+                    result
+                });
+            }
+        }
+    }
+
+    Ok(positions)
+}
+
+impl Tuner {
+    pub fn new(positions: Vec<LabeledPosition>) -> Tuner {
+
+        Tuner {
+            positions,
+            scale: 1.0
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    // the mean squared difference between the results and the winning chances the evaluation predicts
+    pub fn error(&self, params: &EvalParams) -> f64 {
+
+        if self.positions.is_empty() {
+            return 0.0
+        }
+
+        let threads = thread::available_parallelism().map(|n| n.get() ).unwrap_or(1);
+        let chunk = self.positions.len().div_ceil(threads);
+
+        let total: f64 = thread::scope(|scope| {
+            self.positions
+                .chunks(chunk)
+                .map(|positions| scope.spawn(move || positions
+                    .iter()
+                    .map(|p| (p.result - self.winning_chance(params.evaluate(&p.position))).powi(2) )
+                    .sum::<f64>()
+                ))
+                .collect::<Vec<_>>()
+                .into_iter()
+                .map(|handle| handle.join().unwrap_or(0.0) )
+                .sum()
+        });
+
+        total / self.positions.len() as f64
+    }
+
+    // the scale that turns centipawns into winning chances is fitted first and then kept fixed
+    pub fn fit_scale(&mut self, params: &EvalParams) -> f64 {
+
+        let mut best = (self.scale, self.error(params));
+
+        for step in [0.1, 0.01] {
+            let around = best.0;
+            for i in -10..=10 {
+                self.scale = around + step * i as f64;
+                if self.scale <= 0.0 {
+                    continue
+                }
+                let error = self.error(params);
+                if error < best.1 {
+                    best = (self.scale, error);
+                }
+            }
+        }
+
+        self.scale = best.0;
+
+        best.1
+    }
+
+    // tries every parameter one step up and down and keeps the changes that lower the error
+    pub fn tune_pass(
+        &self,
+        params: &mut EvalParams,
+        mut error: f64
+    ) -> f64 {
+
+        for i in 0..params.values_mut().len() {
+            for step in [1, -1] {
+
+                *params.values_mut()[i] += step;
+                let changed = self.error(params);
+
+                if changed < error {
+                    error = changed;
+                    break
+                }
+                *params.values_mut()[i] -= step;
+            }
+        }
+
+        error
+    }
+
+    fn winning_chance(&self, score: i32) -> f64 {
+        1.0 / (1.0 + 10f64.powf(-self.scale * score as f64 / 400.0))
+    }
+}
+
+fn label(line: &str) -> Option<f64> {
+
+    let value = match (line.find("c9"), line.rfind('[')) {
+        (Some(i), _) => line[i + 2..].trim_start().trim_start_matches('"').split(['"', ';']).next(),
+        (None, Some(i)) => line[i + 1..].split(']').next(),
+        (None, None) => None
+    };
+
+    value.and_then(|v| result_value(v.trim()) )
+}
+
+fn result_value(result: &str) -> Option<f64> {
+
+    match result {
+        "1-0" | "1.0" | "1" => Some(1.0),
+        "0-1" | "0.0" | "0" => Some(0.0),
+        "1/2-1/2" | "0.5" => Some(0.5),
+        _ => None
+    }
+}
+
+// errors of a single line report offsets into the whole file
+fn shifted(e: ChessError, by: usize) -> ChessError {
+
+    match e {
+        ChessError::UnableToParseInput {
+            notation,
+            offset,
+            found
+        } => ChessError::UnableToParseInput {
+            notation,
+            offset: offset + by,
+            found
+        },
+        e => e
+    }
+}
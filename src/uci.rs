@@ -0,0 +1,320 @@
+use std::{
+    io::{
+        self,
+        BufRead
+    },
+    sync::{
+        atomic::{
+            AtomicBool,
+            Ordering
+        },
+        Arc
+    },
+    thread::{
+        self,
+        JoinHandle
+    },
+    time::{
+        Duration,
+        Instant
+    }
+};
+use crate::{
+    chess::Chess,
+    position::Position,
+    r#move::Move,
+    piece::Piece,
+    player::Player,
+    square::Square,
+    error::{
+        ChessError,
+        Notation
+    },
+    engine::{
+        Engine,
+        SearchLimits,
+        SearchResult,
+        TimeControl,
+        MATE,
+        MAX_THREADS
+    },
+    difficulty::Difficulty,
+    book::Book,
+    tt::DEFAULT_SIZE_MB
+};
+
+const MAX_HASH_MB: usize = 4096;
+
+// the Universal Chess Interface, for playing the engine from a chess GUI
+pub struct Uci {
+    engine: Arc<Engine>,
+    chess: Chess,
+    search: Option<JoinHandle<()>>,
+    // ends the search in progress, made before its thread starts
+    stop: Arc<AtomicBool>
+}
+
+impl Move {
+    // long algebraic notation without separators, e.g. "e2e4" or "e7e8q"
+    pub fn to_uci(&self) -> String {
+
+        let promotion = match self.promotion {
+            Some(Piece::Queen(_)) => "q",
+            Some(Piece::Rook(_)) => "r",
+            Some(Piece::Bishob(_)) => "b",
+            Some(Piece::Knight(_)) => "n",
+            _ => ""
+        };
+
+        format!("{}{}{}", self.from, self.to, promotion).to_lowercase()
+    }
+}
+
+impl Chess {
+    pub fn move_from_uci(&self, notation: &str) -> Result<Move, ChessError> {
+
+        let err = |offset: usize| ChessError::UnableToParseInput {
+            notation: Notation::Move,
+            offset,
+            found: notation.get(offset..).unwrap_or_default().to_string()
+        };
+
+        if !notation.is_ascii() || !(4..=5).contains(&notation.len()) {
+            return Err(err(0))
+        }
+
+        let from: Square = notation[0..2].parse().map_err(|_| err(0) )?;
+        let to: Square = notation[2..4].parse().map_err(|_| err(2) )?;
+
+        let player = self.position.turn;
+        let promotion = match &notation[4..] {
+            "" => None,
+            "q" => Some(Piece::Queen(player)),
+            "r" => Some(Piece::Rook(player)),
+            "b" => Some(Piece::Bishob(player)),
+            "n" => Some(Piece::Knight(player)),
+            _ => return Err(err(4))
+        };
+
+        let piece = self.position.board[from].ok_or(ChessError::EmptyTile {
+            tile: from
+        })?;
+
+        self.legal_moves()
+            .into_iter()
+            .find(|m| m.from == from && m.to == to && m.promotion == promotion )
+            .ok_or(ChessError::NotAllowedMove {
+                attempted: Move {
+                    piece,
+                    from,
+                    to,
+                    promotion
+                }
+            })
+    }
+}
+
+impl Uci {
+    pub fn new(engine: Engine) -> Uci {
+
+        Uci {
+            engine: Arc::new(engine),
+            chess: Chess::new(),
+            search: None,
+            stop: Arc::default()
+        }
+    }
+
+    pub fn chess(&self) -> &Chess {
+        &self.chess
+    }
+
+    pub fn run(&mut self) -> io::Result<()> {
+
+        for line in io::stdin().lock().lines() {
+            if !self.handle(&line?) {
+                break
+            }
+        }
+
+        self.stop();
+
+        Ok(())
+    }
+
+    // returns false once the GUI asks the engine to quit
+    pub fn handle(&mut self, line: &str) -> bool {
+
+        match line.split_whitespace().collect::<Vec<&str>>().as_slice() {
+            ["uci"] => {
+                println!("id name {} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+                println!("id author {}", env!("CARGO_PKG_AUTHORS"));
+                println!("option name Hash type spin default {} min 1 max {}", DEFAULT_SIZE_MB, MAX_HASH_MB);
+                println!("option name Threads type spin default 1 min 1 max {}", MAX_THREADS);
+                println!(
+                    "option name Difficulty type combo default {} var {}",
+                    Difficulty::Master.name(),
+                    Difficulty::all().map(|d| d.name() ).join(" var ")
+                );
+                println!("option name BookFile type string default <empty>");
+                println!("uciok");
+            },
+            ["isready"] => println!("readyok"),
+            ["setoption", "name", name, "value", value] => self.set_option(name, value),
+            ["ucinewgame"] => {
+                self.stop();
+                self.engine.clear_hash();
+                self.chess = Chess::new();
+            },
+            ["position", "startpos"] => self.set_position(Chess::new(), &[]),
+            ["position", "startpos", "moves", moves @ ..] => self.set_position(Chess::new(), moves),
+            ["position", "fen", fen @ ..] => {
+                let (fen, moves) = match fen.iter().position(|t| *t == "moves" ) {
+                    Some(i) => (&fen[..i], &fen[i + 1..]),
+                    None => (fen, &[][..])
+                };
+                match Position::from_fen(&fen.join(" ")) {
+                    Ok(position) => self.set_position(Chess::from_position(position), moves),
+                    Err(e) => println!("info string {} ({})", e, fen.join(" "))
+                }
+            },
+            ["go", params @ ..] => self.go(params),
+            ["stop"] => self.stop(),
+            ["quit"] => {
+                self.stop();
+                return false
+            },
+            [] => (),
+            _ => println!("info string unknown command '{}'", line.trim())
+        }
+
+        true
+    }
+
+    fn set_option(
+        &mut self,
+        name: &str,
+        value: &str
+    ) {
+
+        self.stop();
+
+        let Some(engine) = Arc::get_mut(&mut self.engine) else {
+            println!("info string invalid value '{}' for option {}", value, name);
+            return
+        };
+
+        let set = if name.eq_ignore_ascii_case("Threads") {
+            value.parse().map(|n| engine.set_threads(n) ).is_ok()
+        } else if name.eq_ignore_ascii_case("Hash") {
+            value.parse::<usize>().map(|mb| engine.set_hash_size(mb.clamp(1, MAX_HASH_MB)) ).is_ok()
+        } else if name.eq_ignore_ascii_case("Difficulty") {
+            Difficulty::from_name(value).map(|d| engine.set_difficulty(d) ).is_some()
+        } else if name.eq_ignore_ascii_case("BookFile") {
+            match value {
+                "<empty>" => {
+                    engine.set_book(None);
+                    true
+                },
+                path => Book::open(path).map(|book| engine.set_book(Some(book)) ).is_ok()
+            }
+        } else {
+            println!("info string unknown option {}", name);
+            return
+        };
+
+        if !set {
+            println!("info string invalid value '{}' for option {}", value, name);
+        }
+    }
+
+    fn set_position(
+        &mut self,
+        chess: Chess,
+        moves: &[&str]
+    ) {
+
+        self.chess = chess;
+
+        for notation in moves {
+            let played = self.chess
+                .move_from_uci(notation)
+                .and_then(|m| self.chess.make_move(&m) )
+            ;
+            if let Err(e) = played {
+                println!("info string {} ({})", e, notation);
+                break
+            }
+        }
+    }
+
+    fn go(&mut self, params: &[&str]) {
+
+        self.wait();
+
+        let value = |name: &str| params
+            .iter()
+            .position(|p| *p == name )
+            .and_then(|i| params.get(i + 1) )
+            .and_then(|v| v.parse::<u64>().ok() )
+        ;
+        let millis = |name: &str| value(name).map(Duration::from_millis);
+
+        let (time, increment) = match self.chess.position.turn {
+            Player::White => ("wtime", "winc"),
+            Player::Black => ("btime", "binc")
+        };
+
+        let limits = SearchLimits {
+            depth: value("depth").map(|d| d as u32),
+            nodes: value("nodes"),
+            move_time: millis("movetime"),
+            clock: millis(time).map(|remaining| TimeControl {
+                remaining,
+                increment: millis(increment).unwrap_or_default(),
+                moves_to_go: value("movestogo").map(|m| m as u32)
+            })
+        };
+
+        let engine = Arc::clone(&self.engine);
+        let chess = self.chess.clone();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        self.stop = Arc::clone(&stop);
+        self.search = Some(thread::spawn(move || {
+
+            let start = Instant::now();
+            let result = engine.search_with_stop(&chess, &limits, &stop);
+            let nps = result.nodes as u128 * 1000 / start.elapsed().as_millis().max(1);
+
+            println!("info depth {} score {} nodes {} nps {}", result.depth, uci_score(&result), result.nodes, nps);
+            match result.best_move {
+                Some(m) => println!("bestmove {}", m.to_uci()),
+                None => println!("bestmove 0000")
+            }
+        }));
+    }
+
+    fn stop(&mut self) {
+
+        self.stop.store(true, Ordering::Relaxed);
+        self.wait();
+    }
+
+    fn wait(&mut self) {
+
+        if let Some(search) = self.search.take() {
+            let _ = search.join();
+        }
+    }
+}
+
+fn uci_score(result: &SearchResult) -> String {
+
+    if result.is_mate() {
+        let moves = (MATE - result.score.abs() + 1) / 2;
+        format!("mate {}", moves * result.score.signum())
+    } else {
+        format!("cp {}", result.score)
+    }
+}